@@ -0,0 +1,168 @@
+//! Times `SpatialHash` and `QuadTree` queries against a linear scan over the same items.
+//!
+//! Run with `cargo bench --bench spatial`.
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/crates/geometry/mod.rs"]
+mod geometry;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use geometry::spatial::{QuadTree, SpatialHash};
+use geometry::{Rect, Vec2};
+
+const WORLD: f32 = 1000.0;
+const QUERIES: usize = 200;
+
+// xorshift, so every run measures the same scene
+struct Random(u32);
+
+impl Random {
+    fn next(&mut self, range: f32) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 % 100_000) as f32 / 100_000.0 * range
+    }
+
+    fn point(&mut self) -> Vec2 {
+        Vec2::new(self.next(WORLD), self.next(WORLD))
+    }
+
+    fn rect(&mut self, max_size: f32) -> Rect {
+        Rect::from_min_size(self.point(), Vec2::new(self.next(max_size) + 0.1, self.next(max_size) + 0.1))
+    }
+}
+
+fn distance_squared(bounds: Rect, point: Vec2) -> f32 {
+    bounds.clamp_point(point).distance_squared(point)
+}
+
+/// Average time of one call, repeating the whole batch until it has run for a while.
+fn time<R>(mut run: impl FnMut() -> R) -> Duration {
+    let mut calls = 0;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_millis(200) || calls == 0 {
+        black_box(run());
+        calls += 1;
+    }
+    start.elapsed() / calls
+}
+
+fn report(operation: &str, items: usize, per: usize, brute_force: Duration, hash: Duration, tree: Duration) {
+    let micros = |duration: Duration| duration.as_secs_f64() * 1e6 / per as f64;
+    println!(
+        "{:<12} {:>6} {:>14.2} {:>14.2} {:>14.2} {:>9.1}x {:>9.1}x",
+        operation,
+        items,
+        micros(brute_force),
+        micros(hash),
+        micros(tree),
+        brute_force.as_secs_f64() / hash.as_secs_f64(),
+        brute_force.as_secs_f64() / tree.as_secs_f64(),
+    );
+}
+
+fn bench(items: usize) {
+    let mut random = Random(0x5eed);
+    let rects: Vec<Rect> = (0..items).map(|_| random.rect(10.0)).collect();
+
+    let mut hash = SpatialHash::new(16.0);
+    let mut tree = QuadTree::new(Rect::new(Vec2::ZERO, Vec2::splat(WORLD + 10.0)));
+    for (i, bounds) in rects.iter().enumerate() {
+        hash.insert(*bounds, i);
+        tree.insert(*bounds, i);
+    }
+
+    let areas: Vec<Rect> = (0..QUERIES).map(|_| random.rect(50.0)).collect();
+    let points: Vec<Vec2> = (0..QUERIES).map(|_| random.point()).collect();
+    let radius = 25.0;
+
+    report(
+        "query_rect",
+        items,
+        QUERIES,
+        time(|| areas.iter().map(|area| rects.iter().filter(|bounds| bounds.intersects(*area)).count()).sum::<usize>()),
+        time(|| areas.iter().map(|area| hash.query_rect(*area).len()).sum::<usize>()),
+        time(|| areas.iter().map(|area| tree.query_rect(*area).len()).sum::<usize>()),
+    );
+
+    report(
+        "query_radius",
+        items,
+        QUERIES,
+        time(|| {
+            points
+                .iter()
+                .map(|point| rects.iter().filter(|bounds| distance_squared(**bounds, *point) <= radius * radius).count())
+                .sum::<usize>()
+        }),
+        time(|| points.iter().map(|point| hash.query_radius(*point, radius).len()).sum::<usize>()),
+        time(|| points.iter().map(|point| tree.query_radius(*point, radius).len()).sum::<usize>()),
+    );
+
+    report(
+        "nearest",
+        items,
+        QUERIES,
+        time(|| {
+            points
+                .iter()
+                .map(|point| {
+                    (0..rects.len()).min_by(|a, b| {
+                        distance_squared(rects[*a], *point).total_cmp(&distance_squared(rects[*b], *point))
+                    })
+                })
+                .collect::<Vec<_>>()
+        }),
+        time(|| points.iter().map(|point| hash.nearest(*point)).collect::<Vec<_>>()),
+        time(|| points.iter().map(|point| tree.nearest(*point)).collect::<Vec<_>>()),
+    );
+
+    // well outside the populated cells, where the hash has to skip past empty rings
+    let far_points: Vec<Vec2> = points.iter().map(|point| *point + Vec2::new(WORLD * 20.0, -WORLD * 10.0)).collect();
+    report(
+        "nearest far",
+        items,
+        QUERIES,
+        time(|| {
+            far_points
+                .iter()
+                .map(|point| {
+                    (0..rects.len()).min_by(|a, b| {
+                        distance_squared(rects[*a], *point).total_cmp(&distance_squared(rects[*b], *point))
+                    })
+                })
+                .collect::<Vec<_>>()
+        }),
+        time(|| far_points.iter().map(|point| hash.nearest(*point)).collect::<Vec<_>>()),
+        time(|| far_points.iter().map(|point| tree.nearest(*point)).collect::<Vec<_>>()),
+    );
+
+    report(
+        "pairs",
+        items,
+        1,
+        time(|| {
+            let mut pairs = 0;
+            for (i, a) in rects.iter().enumerate() {
+                pairs += rects[i + 1..].iter().filter(|b| a.intersects(**b)).count();
+            }
+            pairs
+        }),
+        time(|| hash.pairs().len()),
+        time(|| tree.pairs().len()),
+    );
+}
+
+fn main() {
+    println!("microseconds per call, items up to 10 units wide in a {0}x{0} world", WORLD);
+    println!(
+        "{:<12} {:>6} {:>14} {:>14} {:>14} {:>10} {:>10}",
+        "operation", "items", "linear scan", "spatial hash", "quadtree", "hash gain", "tree gain"
+    );
+    for items in [100, 1_000, 10_000] {
+        bench(items);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use super::texture::Texture;
+use super::upload::UploadBatch;
+
+use crate::geometry::{Rect, Vec2};
+
+use image::{ImageError, RgbaImage};
+use vulkano::image::MipmapsCount;
+
+#[derive(Debug)]
+pub enum AtlasError {
+    Image(ImageError),
+    /// The images do not fit into a texture of the largest allowed size.
+    TooLarge { max_size: u32 },
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtlasError::Image(error) => write!(f, "failed to load atlas image: {}", error),
+            AtlasError::TooLarge { max_size } => write!(f, "images do not fit into a {0}x{0} atlas", max_size),
+        }
+    }
+}
+
+impl Error for AtlasError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AtlasError::Image(error) => Some(error),
+            AtlasError::TooLarge { .. } => None,
+        }
+    }
+}
+
+impl From<ImageError> for AtlasError {
+    fn from(error: ImageError) -> AtlasError {
+        AtlasError::Image(error)
+    }
+}
+
+/// Where one image ended up in an atlas.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AtlasRegion {
+    /// Texture coordinates of the image, ready for `Sprite::with_uvs`.
+    pub uvs: Rect,
+    /// Top left pixel of the image in the atlas.
+    pub origin: [u32; 2],
+    /// Size of the image in pixels.
+    pub dimensions: [u32; 2],
+}
+
+/// Collects loose images to pack into one texture.
+#[derive(Debug, Clone)]
+pub struct AtlasBuilder {
+    images: Vec<(String, RgbaImage)>,
+    padding: u32,
+    max_size: u32,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> AtlasBuilder {
+        AtlasBuilder::new()
+    }
+}
+
+impl AtlasBuilder {
+    /// Two pixels of padding and at most 4096x4096, which every device supports.
+    pub fn new() -> AtlasBuilder {
+        AtlasBuilder { images: Vec::new(), padding: 2, max_size: 4096 }
+    }
+
+    /// Pixels around each image filled with copies of its edges, so filtering does not pick up the neighbouring
+    /// images. Each mip level halves the padding, so the atlas only gets `1 + log2(padding)` levels, or fewer if it is
+    /// too small for that many, and sprites drawn much smaller than their images alias instead of blurring.
+    pub fn with_padding(self, padding: u32) -> AtlasBuilder {
+        AtlasBuilder { padding, ..self }
+    }
+
+    pub fn with_max_size(self, max_size: u32) -> AtlasBuilder {
+        AtlasBuilder { max_size, ..self }
+    }
+
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Adds an image under `name`, replacing any earlier image with the same name.
+    pub fn add(&mut self, name: &str, image: RgbaImage) {
+        match self.images.iter_mut().find(|(existing, _)| existing == name) {
+            Some(entry) => entry.1 = image,
+            None => self.images.push((name.to_string(), image)),
+        }
+    }
+
+    /// Adds an image file, named after the file without its extension.
+    pub fn add_file<P: AsRef<Path>>(&mut self, file: P) -> Result<(), AtlasError> {
+        let file = file.as_ref();
+        let name = file.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        self.add(&name, image::open(file)?.to_rgba8());
+        Ok(())
+    }
+
+    /// Packs the images into one image, without uploading it.
+    pub fn pack(&self) -> Result<(RgbaImage, HashMap<String, AtlasRegion>), AtlasError> {
+        let sizes: Vec<[u32; 2]> = self
+            .images
+            .iter()
+            .map(|(_, image)| [image.width() + self.padding * 2, image.height() + self.padding * 2])
+            .collect();
+
+        let (dimensions, origins) =
+            pack_shelves(&sizes, self.max_size).ok_or(AtlasError::TooLarge { max_size: self.max_size })?;
+
+        let mut atlas = RgbaImage::new(dimensions[0], dimensions[1]);
+        let mut regions = HashMap::with_capacity(self.images.len());
+        let atlas_size = Vec2::new(dimensions[0] as f32, dimensions[1] as f32);
+
+        for ((name, image), padded_origin) in self.images.iter().zip(origins) {
+            let origin = [padded_origin[0] + self.padding, padded_origin[1] + self.padding];
+            copy_extruded(image, &mut atlas, origin, self.padding);
+
+            let min = Vec2::new(origin[0] as f32, origin[1] as f32);
+            let size = Vec2::new(image.width() as f32, image.height() as f32);
+            regions.insert(
+                name.clone(),
+                AtlasRegion {
+                    uvs: Rect { min: min / atlas_size, max: (min + size) / atlas_size },
+                    origin,
+                    dimensions: image.dimensions().into(),
+                },
+            );
+        }
+
+        Ok((atlas, regions))
+    }
+
+    /// Levels whose texels stay within the padding, past which the images would bleed into each other, and no more
+    /// than an atlas of this size can have.
+    fn mip_levels(&self, dimensions: [u32; 2]) -> u32 {
+        let largest = dimensions[0].max(dimensions[1]).max(1);
+        1 + self.padding.checked_ilog2().unwrap_or(0).min(largest.ilog2())
+    }
+
+    /// Packs the images and records the upload of the atlas into `batch`.
+    pub fn build(&self, batch: &mut UploadBatch) -> Result<TextureAtlas, AtlasError> {
+        let (image, regions) = self.pack()?;
+        let mipmaps = MipmapsCount::Specific(self.mip_levels(image.dimensions().into()));
+        let texture = Texture::from_rgba8_with_mipmaps(image.as_raw(), image.dimensions().into(), mipmaps, batch);
+        Ok(TextureAtlas { texture, regions })
+    }
+}
+
+/// One texture holding many images, looked up by the name they were added under.
+#[derive(Debug, Clone)]
+pub struct TextureAtlas {
+    texture: Texture,
+    regions: HashMap<String, AtlasRegion>,
+}
+
+impl TextureAtlas {
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn region(&self, name: &str) -> Option<AtlasRegion> {
+        self.regions.get(name).copied()
+    }
+
+    pub fn uvs(&self, name: &str) -> Option<Rect> {
+        self.regions.get(name).map(|region| region.uvs)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.regions.keys().map(String::as_str)
+    }
+}
+
+/// Places rects in rows from the tallest down, in the smallest power of two texture they fit in. Returns the texture
+/// size and the top left corner of every rect, in the order they were given.
+fn pack_shelves(sizes: &[[u32; 2]], max_size: u32) -> Option<([u32; 2], Vec<[u32; 2]>)> {
+    let area: u64 = sizes.iter().map(|size| size[0] as u64 * size[1] as u64).sum();
+    let widest = sizes.iter().map(|size| size[0]).max().unwrap_or(1);
+    let tallest = sizes.iter().map(|size| size[1]).max().unwrap_or(1);
+
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i][1]));
+
+    let side = ((area as f64).sqrt().ceil() as u32).max(1).next_power_of_two();
+    let mut dimensions = [side.max(widest.next_power_of_two()), side.max(tallest.next_power_of_two())];
+
+    while dimensions[0] <= max_size && dimensions[1] <= max_size {
+        if let Some(origins) = place_on_shelves(sizes, &order, dimensions) {
+            return Some((dimensions, origins));
+        }
+
+        // grow the shorter side, keeping the texture close to square
+        if dimensions[0] <= dimensions[1] {
+            dimensions[0] *= 2;
+        } else {
+            dimensions[1] *= 2;
+        }
+    }
+
+    None
+}
+
+fn place_on_shelves(sizes: &[[u32; 2]], order: &[usize], dimensions: [u32; 2]) -> Option<Vec<[u32; 2]>> {
+    let mut origins = vec![[0, 0]; sizes.len()];
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+
+    for &i in order {
+        let [width, height] = sizes[i];
+        if x + width > dimensions[0] {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+        if x + width > dimensions[0] || y + height > dimensions[1] {
+            return None;
+        }
+
+        origins[i] = [x, y];
+        x += width;
+        shelf_height = shelf_height.max(height);
+    }
+
+    Some(origins)
+}
+
+/// Copies `image` to `origin` in `atlas` and repeats its edge pixels `padding` times around it.
+fn copy_extruded(image: &RgbaImage, atlas: &mut RgbaImage, origin: [u32; 2], padding: u32) {
+    if image.width() == 0 || image.height() == 0 {
+        return;
+    }
+
+    let padding = padding as i64;
+    for y in -padding..image.height() as i64 + padding {
+        for x in -padding..image.width() as i64 + padding {
+            let source_x = x.clamp(0, image.width() as i64 - 1) as u32;
+            let source_y = y.clamp(0, image.height() as i64 - 1) as u32;
+            let pixel = *image.get_pixel(source_x, source_y);
+            atlas.put_pixel((origin[0] as i64 + x) as u32, (origin[1] as i64 + y) as u32, pixel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_packed(sizes: &[[u32; 2]], dimensions: [u32; 2], origins: &[[u32; 2]]) {
+        assert_eq!(origins.len(), sizes.len());
+        let rects: Vec<([u32; 2], [u32; 2])> = origins.iter().zip(sizes).map(|(origin, size)| (*origin, *size)).collect();
+
+        for (i, (origin, size)) in rects.iter().enumerate() {
+            assert!(origin[0] + size[0] <= dimensions[0] && origin[1] + size[1] <= dimensions[1], "{:?} leaves the atlas", i);
+
+            for (other_origin, other_size) in &rects[i + 1..] {
+                let apart = origin[0] + size[0] <= other_origin[0]
+                    || other_origin[0] + other_size[0] <= origin[0]
+                    || origin[1] + size[1] <= other_origin[1]
+                    || other_origin[1] + other_size[1] <= origin[1];
+                assert!(apart, "{:?} overlaps another rect", i);
+            }
+        }
+    }
+
+    #[test]
+    fn packs_within_the_atlas() {
+        let sizes = [[20, 36], [36, 36], [8, 8], [68, 12], [12, 68], [36, 20], [4, 4], [20, 20], [20, 20], [1, 1]];
+        let (dimensions, origins) = pack_shelves(&sizes, 4096).unwrap();
+
+        assert!(dimensions[0].is_power_of_two() && dimensions[1].is_power_of_two());
+        assert_packed(&sizes, dimensions, &origins);
+    }
+
+    #[test]
+    fn packs_many_equal_images() {
+        let sizes = [[36, 36]; 50];
+        let (dimensions, origins) = pack_shelves(&sizes, 4096).unwrap();
+
+        assert_eq!(dimensions, [512, 256]);
+        assert_packed(&sizes, dimensions, &origins);
+    }
+
+    #[test]
+    fn packs_nothing() {
+        assert_eq!(pack_shelves(&[], 4096), Some(([1, 1], Vec::new())));
+    }
+
+    #[test]
+    fn rejects_images_that_do_not_fit() {
+        assert_eq!(pack_shelves(&[[65, 10]], 64), None);
+        assert_eq!(pack_shelves(&[[40, 40]; 5], 64), None);
+        assert!(pack_shelves(&[[32, 32]; 4], 64).is_some());
+
+        let mut builder = AtlasBuilder::new().with_padding(2).with_max_size(64);
+        builder.add("wide", RgbaImage::new(61, 4));
+        assert!(matches!(builder.pack(), Err(AtlasError::TooLarge { max_size: 64 })));
+    }
+
+    #[test]
+    fn place_on_shelves_fails_when_full() {
+        let sizes = [[10, 10], [10, 10], [10, 10]];
+        assert!(place_on_shelves(&sizes, &[0, 1, 2], [20, 10]).is_none());
+        assert_eq!(place_on_shelves(&sizes, &[2, 0, 1], [20, 20]), Some(vec![[10, 0], [0, 10], [0, 0]]));
+    }
+
+    #[test]
+    fn padding_repeats_the_edges() {
+        let mut builder = AtlasBuilder::new().with_padding(2);
+        builder.add("red", RgbaImage::from_pixel(3, 2, image::Rgba([255, 0, 0, 255])));
+        builder.add("blue", RgbaImage::from_pixel(2, 3, image::Rgba([0, 0, 255, 255])));
+        let (atlas, regions) = builder.pack().unwrap();
+
+        for (name, region) in &regions {
+            let [x, y] = region.origin;
+            let [width, height] = region.dimensions;
+            let colour = *atlas.get_pixel(x, y);
+            for padded_y in y - 2..y + height + 2 {
+                for padded_x in x - 2..x + width + 2 {
+                    assert_eq!(*atlas.get_pixel(padded_x, padded_y), colour, "{} bleeds", name);
+                }
+            }
+
+            let size = Vec2::new(atlas.width() as f32, atlas.height() as f32);
+            assert_eq!(region.uvs.min * size, Vec2::new(x as f32, y as f32));
+            assert_eq!(region.uvs.max * size, Vec2::new((x + width) as f32, (y + height) as f32));
+        }
+    }
+
+    #[test]
+    fn mip_levels_stay_within_the_padding() {
+        let size = [256, 128];
+        assert_eq!(AtlasBuilder::new().with_padding(0).mip_levels(size), 1);
+        assert_eq!(AtlasBuilder::new().with_padding(1).mip_levels(size), 1);
+        assert_eq!(AtlasBuilder::new().with_padding(2).mip_levels(size), 2);
+        assert_eq!(AtlasBuilder::new().with_padding(7).mip_levels(size), 3);
+        assert_eq!(AtlasBuilder::new().with_padding(8).mip_levels(size), 4);
+    }
+
+    #[test]
+    fn mip_levels_fit_the_atlas() {
+        // a 1x1 atlas only has its full size level, however wide the padding
+        let empty = AtlasBuilder::new();
+        let (atlas, regions) = empty.pack().unwrap();
+        assert_eq!(atlas.dimensions(), (1, 1));
+        assert!(regions.is_empty());
+        assert_eq!(empty.mip_levels(atlas.dimensions().into()), 1);
+
+        let padded = AtlasBuilder::new().with_padding(64);
+        assert_eq!(padded.mip_levels([4, 2]), 3);
+        assert_eq!(padded.mip_levels([1024, 1024]), 7);
+        assert_eq!(padded.mip_levels([0, 0]), 1);
+    }
+}
@@ -1,29 +1,64 @@
+use std::ops::Range;
 use std::sync::Arc;
 
-use crate::geometry::Vertex;
+use super::clip::DrawRegion;
+use super::render_target::RenderTarget;
+use super::texture::TEXTURE_SET;
+use super::uniforms::{DrawConstants, FrameUniformBuffers};
+use super::upload::UploadBatch;
+use super::vertex_layout::VertexLayout;
 
-use vulkano::buffer::{BufferUsage, TypedBufferAccess};
+use vulkano::buffer::{BufferAccessObject, BufferUsage, TypedBufferAccess};
+use vulkano::buffer::cpu_pool::{CpuBufferPool, CpuBufferPoolChunk};
 use vulkano::buffer::immutable::ImmutableBuffer;
 use vulkano::command_buffer::{
     AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer, SubpassContents,
 };
 
-use vulkano::device::{Device, Queue};
-use vulkano::image::{SwapchainImage, view::ImageView};
-use vulkano::pipeline::GraphicsPipeline;
-use vulkano::render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass};
-use vulkano::sync::GpuFuture;
+use vulkano::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::{Device, DeviceOwned, Queue};
+use vulkano::image::{AttachmentImage, ImageAccess, ImageViewAbstract, SwapchainImage, view::ImageView};
+use vulkano::memory::pool::StdMemoryPool;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline, PipelineBindPoint};
+use vulkano::render_pass::{Framebuffer, FramebufferCreateInfo, LoadOp, RenderPass};
 use winit::window::Window;
 
-pub fn get_framebuffers(images: &[Arc<SwapchainImage<Window>>], render_pass: &Arc<RenderPass>) -> Vec<Arc<Framebuffer>> {
+/// Framebuffers for a render pass from `get_render_pass` with the same `target`, each with its own multisampled and
+/// depth images when the target has them.
+pub fn get_framebuffers(images: &[Arc<SwapchainImage<Window>>], render_pass: &Arc<RenderPass>, target: &RenderTarget) -> Vec<Arc<Framebuffer>> {
+    let device = render_pass.device().clone();
+
     images
         .iter()
         .map(|image| {
-            let view = ImageView::new_default(image.clone()).unwrap();
+            let dimensions = image.dimensions().width_height();
+            let view: Arc<dyn ImageViewAbstract> = ImageView::new_default(image.clone()).unwrap();
+
+            let mut attachments = if target.is_multisampled() {
+                let format = image.format();
+                let multisampled = if target.load_op == LoadOp::Load {
+                    AttachmentImage::multisampled(device.clone(), dimensions, target.samples, format)
+                } else {
+                    // never read back, so the driver does not have to keep it in memory
+                    AttachmentImage::transient_multisampled(device.clone(), dimensions, target.samples, format)
+                }
+                .expect("failed to create multisampled image");
+
+                vec![ImageView::new_default(multisampled).unwrap(), view]
+            } else {
+                vec![view]
+            };
+
+            if let Some(depth_format) = target.depth_format {
+                let depth = AttachmentImage::transient_multisampled(device.clone(), dimensions, target.samples, depth_format)
+                    .expect("failed to create depth image");
+                attachments.push(ImageView::new_default(depth).unwrap());
+            }
+
             Framebuffer::new(
                 render_pass.clone(),
                 FramebufferCreateInfo {
-                    attachments: vec![view],
+                    attachments,
                     ..Default::default()
                 },
             )
@@ -32,61 +67,176 @@ pub fn get_framebuffers(images: &[Arc<SwapchainImage<Window>>], render_pass: &Ar
         .collect::<Vec<_>>()
 }
 
-pub fn get_command_buffers(device: &Arc<Device>, queue: &Arc<Queue>, pipeline: &Arc<GraphicsPipeline>, framebuffers: &Vec<Arc<Framebuffer>>, vertex_buffer: &Arc<ImmutableBuffer<[Vertex]>>, index_buffer: &Arc<ImmutableBuffer<[u32]>>) -> Vec<Arc<PrimaryAutoCommandBuffer>> {
+/// One indexed draw. The buffers can come from either `create_*_buffer` or `DynamicBuffers`.
+pub struct DrawCall<Vb: ?Sized, Ib> {
+    pub vertex_buffer: Arc<Vb>,
+    pub index_buffer: Arc<Ib>,
+    pub constants: DrawConstants,
+    /// Bound to `TEXTURE_SET` for pipelines that sample a texture, from `Texture::descriptor_set`.
+    pub texture: Option<Arc<PersistentDescriptorSet>>,
+    /// Part of the index buffer to draw, `None` for all of it.
+    pub indices: Option<Range<u32>>,
+}
+
+impl<Vb: ?Sized, Ib> DrawCall<Vb, Ib> {
+    pub fn new(vertex_buffer: Arc<Vb>, index_buffer: Arc<Ib>, constants: DrawConstants) -> DrawCall<Vb, Ib> {
+        DrawCall { vertex_buffer, index_buffer, constants, texture: None, indices: None }
+    }
+
+    pub fn with_texture(self, texture: Arc<PersistentDescriptorSet>) -> DrawCall<Vb, Ib> {
+        DrawCall { texture: Some(texture), ..self }
+    }
+
+    pub fn with_indices(self, indices: Range<u32>) -> DrawCall<Vb, Ib> {
+        DrawCall { indices: Some(indices), ..self }
+    }
+}
+
+/// Records every draw in order into one command buffer per framebuffer, binding the uniforms of the matching image.
+///
+/// Suits draws that stay the same from frame to frame, draws of data uploaded every frame are better recorded for the
+/// acquired image only with `get_command_buffer`.
+pub fn get_command_buffers<Vb, Ib>(queue: &Arc<Queue>, pipeline: &Arc<GraphicsPipeline>, framebuffers: &[Arc<Framebuffer>], target: &RenderTarget, region: &DrawRegion, uniforms: &FrameUniformBuffers, draws: &[DrawCall<Vb, Ib>]) -> Vec<Arc<PrimaryAutoCommandBuffer>>
+where
+    Vb: ?Sized,
+    Arc<Vb>: BufferAccessObject,
+    Ib: TypedBufferAccess<Content = [u32]> + 'static,
+{
     framebuffers
         .iter()
-        .map(|framebuffer| {
-            let mut builder = AutoCommandBufferBuilder::primary(
-                device.clone(),
-                queue.family(),
-                CommandBufferUsage::MultipleSubmit,
-            )
-            .unwrap();
-
-            builder
-                .begin_render_pass(
-                    framebuffer.clone(),
-                    SubpassContents::Inline,
-                    vec![[0.0, 0.0, 0.0, 1.0].into()], // clear colour
-                )
-                .unwrap()
-                
-                .bind_pipeline_graphics(pipeline.clone())
-                .bind_vertex_buffers(0, vertex_buffer.clone())
-                .bind_index_buffer(index_buffer.clone())
-                .draw_indexed(index_buffer.len() as u32, 1, 0, 0, 0)
-                .unwrap()
-                .end_render_pass()
-                .unwrap();
-
-            Arc::new(builder.build().unwrap())
+        .enumerate()
+        .map(|(image_i, framebuffer)| {
+            get_command_buffer(queue, pipeline, framebuffer, uniforms.descriptor_set(image_i), target, region, draws)
         })
         .collect()
 }
 
-pub fn create_vertex_buffer(vertices: Vec<Vertex>, queue: &Arc<Queue>) -> Arc<ImmutableBuffer<[Vertex]>> {
-    /*CpuAccessibleBuffer::from_iter(
-        device.clone(),
-        BufferUsage::vertex_buffer(),
-        false,
-        vertices.into_iter(),
+/// Records every draw in order into a command buffer for one framebuffer, with `frame_uniforms` from
+/// `FrameUniformBuffers::descriptor_set` for the same image. Buffers and textures shared with the previous draw are not
+/// bound again.
+///
+/// The framebuffer is cleared as `target` says, and the pipeline needs a dynamic viewport and scissor, which are both
+/// set from `region`.
+pub fn get_command_buffer<Vb, Ib>(queue: &Arc<Queue>, pipeline: &Arc<GraphicsPipeline>, framebuffer: &Arc<Framebuffer>, frame_uniforms: &Arc<PersistentDescriptorSet>, target: &RenderTarget, region: &DrawRegion, draws: &[DrawCall<Vb, Ib>]) -> Arc<PrimaryAutoCommandBuffer>
+where
+    Vb: ?Sized,
+    Arc<Vb>: BufferAccessObject,
+    Ib: TypedBufferAccess<Content = [u32]> + 'static,
+{
+    let mut builder = AutoCommandBufferBuilder::primary(
+        queue.device().clone(),
+        queue.family(),
+        CommandBufferUsage::MultipleSubmit,
     )
-    .unwrap()*/
-    let (buffer, future) = ImmutableBuffer::from_iter(
-        vertices.iter().cloned(), BufferUsage::vertex_buffer(),
-        queue.clone())
-        .unwrap();
-        future.flush().unwrap();
-    
+    .unwrap();
+
+    builder
+        .begin_render_pass(
+            framebuffer.clone(),
+            SubpassContents::Inline,
+            target.clear_values(),
+        )
+        .unwrap()
+        .set_viewport(0, [region.viewport.clone()])
+        .set_scissor(0, [region.clip.scissor()])
+        .bind_pipeline_graphics(pipeline.clone())
+        .bind_descriptor_sets(
+            PipelineBindPoint::Graphics,
+            pipeline.layout().clone(),
+            0,
+            frame_uniforms.clone(),
+        );
+
+    let mut previous: Option<&DrawCall<Vb, Ib>> = None;
+    for draw in draws {
+        let same_texture = previous.is_some_and(|previous| match (&previous.texture, &draw.texture) {
+            (Some(previous), Some(texture)) => Arc::ptr_eq(previous, texture),
+            _ => false,
+        });
+        if let (Some(texture), false) = (&draw.texture, same_texture) {
+            builder.bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                pipeline.layout().clone(),
+                TEXTURE_SET,
+                texture.clone(),
+            );
+        }
+
+        if !previous.is_some_and(|previous| Arc::ptr_eq(&previous.vertex_buffer, &draw.vertex_buffer)) {
+            builder.bind_vertex_buffers(0, draw.vertex_buffer.clone());
+        }
+        if !previous.is_some_and(|previous| Arc::ptr_eq(&previous.index_buffer, &draw.index_buffer)) {
+            builder.bind_index_buffer(draw.index_buffer.clone());
+        }
+
+        let indices = draw.indices.clone().unwrap_or(0..draw.index_buffer.len() as u32);
+        builder
+            .push_constants(pipeline.layout().clone(), 0, draw.constants)
+            .draw_indexed(indices.end - indices.start, 1, indices.start, 0, 0)
+            .unwrap();
+
+        previous = Some(draw);
+    }
+
+    builder.end_render_pass().unwrap();
+
+    Arc::new(builder.build().unwrap())
+}
+
+/// Uploads vertices that will not change and waits for them to arrive. Geometry that changes every frame should use
+/// `DynamicBuffers` instead, and many uploads are better recorded together with an `UploadBatch`.
+pub fn create_vertex_buffer<V: VertexLayout>(vertices: Vec<V>, queue: &Arc<Queue>) -> Arc<ImmutableBuffer<[V]>> {
+    let mut batch = UploadBatch::new(queue);
+    let buffer = batch.vertex_buffer(&vertices);
+    batch.submit_and_wait();
+
     buffer
 }
 
 pub fn create_index_buffer(indices: Vec<u32> , queue: &Arc<Queue>) -> Arc<ImmutableBuffer<[u32]>> {
-    let (buffer, future) = ImmutableBuffer::from_iter(
-        indices.iter().cloned(), BufferUsage::index_buffer(),
-        queue.clone())
-        .unwrap();
-        future.flush().unwrap();
-    
+    let mut batch = UploadBatch::new(queue);
+    let buffer = batch.index_buffer(&indices);
+    batch.submit_and_wait();
+
     buffer
+}
+
+/// Slice of a `DynamicBuffers` pool, returned to the pool once it is dropped.
+pub type DynamicChunk<T> = Arc<CpuBufferPoolChunk<T, Arc<StdMemoryPool>>>;
+
+/// Host visible ring buffers for geometry that is rewritten every frame.
+///
+/// Each upload takes fresh space from the pools, and that space is reused once the command buffers drawing it have
+/// been dropped, so frames in flight never see their data overwritten. The pools grow when they run out of room.
+#[derive(Clone)]
+pub struct DynamicBuffers<V: VertexLayout> {
+    vertices: CpuBufferPool<V>,
+    indices: CpuBufferPool<u32>,
+}
+
+impl<V: VertexLayout> DynamicBuffers<V> {
+    pub fn new(device: &Arc<Device>) -> DynamicBuffers<V> {
+        DynamicBuffers {
+            vertices: CpuBufferPool::vertex_buffer(device.clone()),
+            indices: CpuBufferPool::new(device.clone(), BufferUsage::index_buffer()),
+        }
+    }
+
+    /// Allocates room for this many vertices and indices up front, so the first frames do not have to grow the pools.
+    pub fn reserve(&self, vertices: usize, indices: usize) {
+        self.vertices.reserve(vertices as u64).expect("failed to allocate vertex pool");
+        self.indices.reserve(indices as u64).expect("failed to allocate index pool");
+    }
+
+    /// Copies the vertices and indices into the pools, `None` when there is nothing to draw.
+    pub fn upload(&self, vertices: &[V], indices: &[u32]) -> Option<(DynamicChunk<V>, DynamicChunk<u32>)> {
+        if vertices.is_empty() || indices.is_empty() {
+            return None;
+        }
+
+        Some((
+            self.vertices.chunk(vertices.iter().cloned()).expect("failed to allocate vertex chunk"),
+            self.indices.chunk(indices.iter().cloned()).expect("failed to allocate index chunk"),
+        ))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,442 @@
+use super::clip::{ClipRect, DrawRegion};
+
+use std::f32::consts::FRAC_PI_2;
+
+use crate::geometry::{Mat4, Rect, Vec2, Vec3};
+
+/// How a camera's view is fitted into a window with a different aspect ratio.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Letterbox {
+    /// Fills the window and shows at least the view size, with extra world visible along one axis.
+    #[default]
+    Expand,
+    /// Shows exactly the view size at its own aspect ratio, with bars on two sides of the window.
+    Fit,
+    /// Fills the window at the view's aspect ratio, cutting off whatever does not fit.
+    Crop,
+    /// Squashes the view size into the window, distorting it.
+    Stretch,
+}
+
+/// Orthographic camera looking at the xy plane with y pointing up.
+///
+/// Screen positions are in pixels from the top left corner of the window, the same as winit's cursor positions.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera2D {
+    /// World position shown at the centre of the view.
+    pub position: Vec2,
+    /// Counter-clockwise rotation of the camera in radians, the world appears to turn the other way.
+    pub rotation: f32,
+    /// Magnification, 2.0 shows half as much of the world.
+    pub zoom: f32,
+    /// World units visible at a zoom of 1.0.
+    pub view_size: Vec2,
+    pub letterbox: Letterbox,
+    /// How quickly the camera catches up with the target it follows, 0.0 jumps straight to it. After one second
+    /// only `e^-follow_speed` of the distance remains.
+    pub follow_speed: f32,
+    target: Option<Vec2>,
+}
+
+impl Default for Camera2D {
+    /// Shows the same area as clip space, from -1.0 to 1.0 on both axes.
+    fn default() -> Camera2D {
+        Camera2D::new(Vec2::splat(2.0))
+    }
+}
+
+impl Camera2D {
+    pub fn new(view_size: Vec2) -> Camera2D {
+        Camera2D {
+            position: Vec2::ZERO,
+            rotation: 0.0,
+            zoom: 1.0,
+            view_size,
+            letterbox: Letterbox::default(),
+            follow_speed: 5.0,
+            target: None,
+        }
+    }
+
+    pub fn with_position(self, position: Vec2) -> Camera2D {
+        Camera2D { position, ..self }
+    }
+
+    pub fn with_zoom(self, zoom: f32) -> Camera2D {
+        Camera2D { zoom, ..self }
+    }
+
+    pub fn with_rotation(self, rotation: f32) -> Camera2D {
+        Camera2D { rotation, ..self }
+    }
+
+    pub fn with_letterbox(self, letterbox: Letterbox) -> Camera2D {
+        Camera2D { letterbox, ..self }
+    }
+
+    pub fn with_follow_speed(self, follow_speed: f32) -> Camera2D {
+        Camera2D { follow_speed, ..self }
+    }
+
+    /// Moves the camera by a world space offset.
+    pub fn pan(&mut self, offset: Vec2) {
+        self.position += offset;
+    }
+
+    /// Moves the camera so the world follows a cursor dragged by `delta` pixels.
+    pub fn pan_screen(&mut self, delta: Vec2, window: [u32; 2]) {
+        let offset = self.screen_to_world(Vec2::ZERO, window) - self.screen_to_world(delta, window);
+        self.pan(offset);
+    }
+
+    pub fn rotate(&mut self, angle: f32) {
+        self.rotation += angle;
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the centre of the view in place.
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom *= factor;
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the world position under `screen_point` in place.
+    pub fn zoom_at(&mut self, factor: f32, screen_point: Vec2, window: [u32; 2]) {
+        let before = self.screen_to_world(screen_point, window);
+        self.zoom_by(factor);
+        let after = self.screen_to_world(screen_point, window);
+        self.pan(before - after);
+    }
+
+    /// Starts moving towards `target` on every `update`. Call again whenever the target moves.
+    pub fn follow(&mut self, target: Vec2) {
+        self.target = Some(target);
+    }
+
+    pub fn stop_following(&mut self) {
+        self.target = None;
+    }
+
+    pub fn target(&self) -> Option<Vec2> {
+        self.target
+    }
+
+    /// Moves towards the followed target, `delta` being the seconds since the last update.
+    pub fn update(&mut self, delta: f32) {
+        let target = match self.target {
+            Some(target) => target,
+            None => return,
+        };
+
+        if self.follow_speed <= 0.0 {
+            self.position = target;
+        } else {
+            self.position = self.position.lerp(target, 1.0 - (-self.follow_speed * delta).exp());
+        }
+    }
+
+    /// Part of the window the camera draws to. Only `Fit` and `Crop` differ from the whole window.
+    pub fn region(&self, window: [u32; 2]) -> DrawRegion {
+        let full = ClipRect::full(window);
+        let window_size = Vec2::new(window[0] as f32, window[1] as f32);
+        let pixels_per_unit = |scale: fn(f32, f32) -> f32| {
+            scale(window_size.x / self.view_size.x, window_size.y / self.view_size.y)
+        };
+
+        match self.letterbox {
+            Letterbox::Expand | Letterbox::Stretch => DrawRegion::new(full),
+            Letterbox::Fit => {
+                let size = self.view_size * pixels_per_unit(f32::min);
+                let origin = (window_size - size) * 0.5;
+                let bars = ClipRect::new(
+                    origin.x.round() as u32,
+                    origin.y.round() as u32,
+                    size.x.round() as u32,
+                    size.y.round() as u32,
+                );
+                DrawRegion::new(full.intersection(bars).unwrap_or(full))
+            }
+            Letterbox::Crop => {
+                let size = self.view_size * pixels_per_unit(f32::max);
+                let mut region = DrawRegion::new(full);
+                region.viewport.origin = ((window_size - size) * 0.5).to_array();
+                region.viewport.dimensions = size.to_array();
+                region
+            }
+        }
+    }
+
+    /// World units covered by the camera's viewport in this window, after zooming.
+    pub fn visible_size(&self, window: [u32; 2]) -> Vec2 {
+        let size = match self.letterbox {
+            Letterbox::Expand => {
+                let window_size = Vec2::new(window[0].max(1) as f32, window[1].max(1) as f32);
+                let units_per_pixel = (self.view_size.x / window_size.x).max(self.view_size.y / window_size.y);
+                window_size * units_per_pixel
+            }
+            Letterbox::Fit | Letterbox::Crop | Letterbox::Stretch => self.view_size,
+        };
+
+        size / self.zoom
+    }
+
+    /// Smallest world space rect containing everything the camera can see, for culling.
+    pub fn visible_rect(&self, window: [u32; 2]) -> Rect {
+        let half_size = self.visible_size(window) * 0.5;
+        let corners = [
+            Vec2::new(-half_size.x, -half_size.y),
+            Vec2::new(half_size.x, -half_size.y),
+            Vec2::new(half_size.x, half_size.y),
+            Vec2::new(-half_size.x, half_size.y),
+        ]
+        .map(|corner| corner.rotate(self.rotation) + self.position);
+
+        Rect::from_points(&corners).unwrap()
+    }
+
+    /// World to clip space, for `FrameUniforms::view_projection`.
+    pub fn view_projection(&self, window: [u32; 2]) -> Mat4 {
+        let half_size = self.visible_size(window) * 0.5;
+        let projection = Mat4::orthographic(-half_size.x, half_size.x, -half_size.y, half_size.y, -1.0, 1.0);
+        let view = Mat4::from_rotation_z(-self.rotation) * Mat4::from_translation(-self.position.extend(0.0));
+
+        projection * view
+    }
+
+    /// World position drawn at a pixel of the window. Works outside of the viewport as well.
+    ///
+    /// Picks what is under the cursor, using the position from the last `WindowEvent::CursorMoved`:
+    ///
+    /// ```ignore
+    /// let world = camera.screen_to_world(cursor, window.inner_size().into());
+    /// if square.contains_point(world) {
+    ///     // the cursor is over the square
+    /// }
+    /// ```
+    pub fn screen_to_world(&self, screen: Vec2, window: [u32; 2]) -> Vec2 {
+        let viewport = self.region(window).viewport;
+        let origin = Vec2::from(viewport.origin);
+        let dimensions = Vec2::from(viewport.dimensions);
+
+        // from pixels to -1..1 with y pointing up
+        let normalised = (screen - origin) / dimensions * 2.0 - Vec2::ONE;
+        let half_size = self.visible_size(window) * 0.5;
+        let view = Vec2::new(normalised.x * half_size.x, -normalised.y * half_size.y);
+
+        view.rotate(self.rotation) + self.position
+    }
+
+    /// Pixel of the window a world position is drawn at.
+    pub fn world_to_screen(&self, world: Vec2, window: [u32; 2]) -> Vec2 {
+        let viewport = self.region(window).viewport;
+        let origin = Vec2::from(viewport.origin);
+        let dimensions = Vec2::from(viewport.dimensions);
+
+        let view = (world - self.position).rotate(-self.rotation);
+        let half_size = self.visible_size(window) * 0.5;
+        let normalised = Vec2::new(view.x / half_size.x, -view.y / half_size.y);
+
+        origin + (normalised + Vec2::ONE) * 0.5 * dimensions
+    }
+}
+
+/// Furthest a camera can look up or down, just short of straight up where yaw stops meaning anything.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.001;
+
+/// Perspective camera in a right-handed world with y pointing up.
+///
+/// With a yaw and pitch of zero it looks down -z. Positive yaw turns it to the left, positive pitch up.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera3D {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Vertical field of view in radians.
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Default for Camera3D {
+    fn default() -> Camera3D {
+        Camera3D::new(Vec3::ZERO)
+    }
+}
+
+impl Camera3D {
+    pub fn new(position: Vec3) -> Camera3D {
+        Camera3D { position, yaw: 0.0, pitch: 0.0, fov_y: 60.0_f32.to_radians(), near: 0.1, far: 1000.0 }
+    }
+
+    /// Camera at `position` looking towards `target`.
+    pub fn looking_at(position: Vec3, target: Vec3) -> Camera3D {
+        let mut camera = Camera3D::new(position);
+        camera.look_at(target);
+        camera
+    }
+
+    pub fn with_fov(self, fov_y: f32) -> Camera3D {
+        Camera3D { fov_y, ..self }
+    }
+
+    pub fn with_clip_planes(self, near: f32, far: f32) -> Camera3D {
+        Camera3D { near, far, ..self }
+    }
+
+    /// Turns the camera towards `target`, doing nothing if it is at the camera's position.
+    pub fn look_at(&mut self, target: Vec3) {
+        if let Some(direction) = (target - self.position).try_normalize() {
+            self.yaw = (-direction.x).atan2(-direction.z);
+            self.pitch = direction.y.clamp(-1.0, 1.0).asin().clamp(-MAX_PITCH, MAX_PITCH);
+        }
+    }
+
+    /// Adds to the yaw and pitch, never letting the camera tip over.
+    pub fn turn(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Unit vector the camera looks along.
+    pub fn forward(&self) -> Vec3 {
+        direction(self.yaw, self.pitch)
+    }
+
+    /// Unit vector to the right of the view, always horizontal.
+    pub fn right(&self) -> Vec3 {
+        Vec3::new(self.yaw.cos(), 0.0, -self.yaw.sin())
+    }
+
+    /// Unit vector to the top of the view.
+    pub fn up(&self) -> Vec3 {
+        self.right().cross(self.forward())
+    }
+
+    /// World to camera space.
+    pub fn view(&self) -> Mat4 {
+        Mat4::from_rotation_x(-self.pitch) * Mat4::from_rotation_y(-self.yaw) * Mat4::from_translation(-self.position)
+    }
+
+    pub fn projection(&self, aspect_ratio: f32) -> Mat4 {
+        Mat4::perspective(self.fov_y, aspect_ratio, self.near, self.far)
+    }
+
+    /// World to clip space for a window of this size, for `FrameUniforms::view_projection`.
+    pub fn view_projection(&self, window: [u32; 2]) -> Mat4 {
+        self.projection(aspect_ratio(window)) * self.view()
+    }
+
+    /// Origin and unit direction of the ray through a pixel of the window, for picking with the mouse.
+    pub fn screen_ray(&self, screen: Vec2, window: [u32; 2]) -> (Vec3, Vec3) {
+        let normalised = Vec2::new(
+            screen.x / window[0].max(1) as f32 * 2.0 - 1.0,
+            screen.y / window[1].max(1) as f32 * 2.0 - 1.0,
+        );
+
+        let inverse = self.view_projection(window).inverse().expect("camera has a degenerate projection");
+        let near = inverse.transform_point(normalised.extend(0.0));
+        let far = inverse.transform_point(normalised.extend(1.0));
+
+        (near, (far - near).normalize())
+    }
+
+    /// Pixel of the window a world position is drawn at, `None` if it is behind the camera.
+    pub fn world_to_screen(&self, world: Vec3, window: [u32; 2]) -> Option<Vec2> {
+        let clip = self.view_projection(window) * world.extend(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        let normalised = Vec2::new(clip.x / clip.w, clip.y / clip.w);
+        Some((normalised + Vec2::ONE) * 0.5 * Vec2::new(window[0] as f32, window[1] as f32))
+    }
+}
+
+/// Free-flying first person controls.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FlyController {
+    /// World units per second.
+    pub speed: f32,
+    /// Radians turned per pixel of mouse movement.
+    pub sensitivity: f32,
+}
+
+impl Default for FlyController {
+    fn default() -> FlyController {
+        FlyController { speed: 5.0, sensitivity: 0.003 }
+    }
+}
+
+impl FlyController {
+    /// Turns by a mouse movement in pixels and moves along `movement`, given as right, up and forward relative to
+    /// the camera, for `delta` seconds.
+    pub fn update(&self, camera: &mut Camera3D, movement: Vec3, mouse: Vec2, delta: f32) {
+        camera.turn(-mouse.x * self.sensitivity, -mouse.y * self.sensitivity);
+
+        let offset = camera.right() * movement.x + Vec3::Y * movement.y + camera.forward() * movement.z;
+        if let Some(direction) = offset.try_normalize() {
+            camera.position += direction * (self.speed * delta);
+        }
+    }
+}
+
+/// Keeps the camera circling around and looking at a target.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OrbitController {
+    pub target: Vec3,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+impl OrbitController {
+    pub fn new(target: Vec3, distance: f32) -> OrbitController {
+        OrbitController { target, distance, yaw: 0.0, pitch: 0.0, min_distance: 0.1, max_distance: 1000.0 }
+    }
+
+    /// Orbit matching the camera's current position, looking at `target` from there.
+    pub fn from_camera(camera: &Camera3D, target: Vec3) -> OrbitController {
+        let mut looking = *camera;
+        looking.look_at(target);
+
+        OrbitController {
+            yaw: looking.yaw,
+            pitch: looking.pitch,
+            ..OrbitController::new(target, camera.position.distance(target))
+        }
+    }
+
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Moves closer for factors below 1.0 and further away above.
+    pub fn zoom(&mut self, factor: f32) {
+        self.distance = (self.distance * factor).clamp(self.min_distance, self.max_distance);
+    }
+
+    /// Moves the target along the view plane, right and up as the camera sees them.
+    pub fn pan(&mut self, offset: Vec2) {
+        let forward = direction(self.yaw, self.pitch);
+        let right = Vec3::new(self.yaw.cos(), 0.0, -self.yaw.sin());
+        self.target += right * offset.x + right.cross(forward) * offset.y;
+    }
+
+    /// Places the camera on the orbit, looking at the target.
+    pub fn apply(&self, camera: &mut Camera3D) {
+        camera.yaw = self.yaw;
+        camera.pitch = self.pitch;
+        camera.position = self.target - direction(self.yaw, self.pitch) * self.distance;
+    }
+}
+
+fn direction(yaw: f32, pitch: f32) -> Vec3 {
+    let (sin_yaw, cos_yaw) = yaw.sin_cos();
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    Vec3::new(-sin_yaw * cos_pitch, sin_pitch, -cos_yaw * cos_pitch)
+}
+
+fn aspect_ratio(window: [u32; 2]) -> f32 {
+    window[0].max(1) as f32 / window[1].max(1) as f32
+}
@@ -0,0 +1,131 @@
+use vulkano::pipeline::graphics::viewport::{Scissor, Viewport};
+
+/// Rectangle of the framebuffer in pixels, measured from the top left corner.
+///
+/// Used as a viewport it maps the whole of clip space onto the rectangle (split screen), used as a scissor it only
+/// cuts off whatever falls outside of it (UI panels).
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ClipRect {
+    pub origin: [u32; 2],
+    pub dimensions: [u32; 2],
+}
+
+impl ClipRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> ClipRect {
+        ClipRect { origin: [x, y], dimensions: [width, height] }
+    }
+
+    /// The whole of a framebuffer with these dimensions.
+    pub fn full(dimensions: [u32; 2]) -> ClipRect {
+        ClipRect { origin: [0, 0], dimensions }
+    }
+
+    pub fn width(self) -> u32 {
+        self.dimensions[0]
+    }
+
+    pub fn height(self) -> u32 {
+        self.dimensions[1]
+    }
+
+    /// Bottom right corner, just outside the rect.
+    pub fn end(self) -> [u32; 2] {
+        [self.origin[0] + self.dimensions[0], self.origin[1] + self.dimensions[1]]
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.dimensions[0] == 0 || self.dimensions[1] == 0
+    }
+
+    pub fn contains(self, x: u32, y: u32) -> bool {
+        let end = self.end();
+        x >= self.origin[0] && x < end[0] && y >= self.origin[1] && y < end[1]
+    }
+
+    /// Pixels inside both rects, `None` if they do not overlap. Nested panels clip their children with this.
+    pub fn intersection(self, other: ClipRect) -> Option<ClipRect> {
+        let (end, other_end) = (self.end(), other.end());
+        let min = [self.origin[0].max(other.origin[0]), self.origin[1].max(other.origin[1])];
+        let max = [end[0].min(other_end[0]), end[1].min(other_end[1])];
+        if max[0] <= min[0] || max[1] <= min[1] {
+            return None;
+        }
+
+        Some(ClipRect { origin: min, dimensions: [max[0] - min[0], max[1] - min[1]] })
+    }
+
+    /// Splits the rect into `count` side by side columns, the last one taking any leftover pixels.
+    pub fn split_columns(self, count: u32) -> Vec<ClipRect> {
+        let width = self.width() / count.max(1);
+        (0..count)
+            .map(|i| {
+                let x = self.origin[0] + i * width;
+                let w = if i + 1 == count { self.end()[0] - x } else { width };
+                ClipRect::new(x, self.origin[1], w, self.height())
+            })
+            .collect()
+    }
+
+    /// Splits the rect into `count` stacked rows, the last one taking any leftover pixels.
+    pub fn split_rows(self, count: u32) -> Vec<ClipRect> {
+        let height = self.height() / count.max(1);
+        (0..count)
+            .map(|i| {
+                let y = self.origin[1] + i * height;
+                let h = if i + 1 == count { self.end()[1] - y } else { height };
+                ClipRect::new(self.origin[0], y, self.width(), h)
+            })
+            .collect()
+    }
+
+    /// Viewport drawing the whole scene into this rect.
+    pub fn viewport(self) -> Viewport {
+        Viewport {
+            origin: [self.origin[0] as f32, self.origin[1] as f32],
+            dimensions: [self.dimensions[0] as f32, self.dimensions[1] as f32],
+            depth_range: 0.0..1.0,
+        }
+    }
+
+    pub fn scissor(self) -> Scissor {
+        Scissor { origin: self.origin, dimensions: self.dimensions }
+    }
+}
+
+impl From<ClipRect> for Scissor {
+    fn from(clip: ClipRect) -> Scissor {
+        clip.scissor()
+    }
+}
+
+impl From<ClipRect> for Viewport {
+    fn from(clip: ClipRect) -> Viewport {
+        clip.viewport()
+    }
+}
+
+/// Where draws land in the framebuffer: the viewport clip space is mapped onto, and the clip rect cutting off
+/// anything outside of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawRegion {
+    pub viewport: Viewport,
+    pub clip: ClipRect,
+}
+
+impl DrawRegion {
+    /// Draws the whole scene into `rect` and nowhere else.
+    pub fn new(rect: ClipRect) -> DrawRegion {
+        DrawRegion { viewport: rect.viewport(), clip: rect }
+    }
+
+    /// The whole of a framebuffer with these dimensions.
+    pub fn full(dimensions: [u32; 2]) -> DrawRegion {
+        DrawRegion::new(ClipRect::full(dimensions))
+    }
+
+    /// Same viewport, but only drawing where `clip` overlaps the current clip rect.
+    pub fn clipped(&self, clip: ClipRect) -> DrawRegion {
+        let clip = self.clip.intersection(clip).unwrap_or(ClipRect::new(clip.origin[0], clip.origin[1], 0, 0));
+        DrawRegion { viewport: self.viewport.clone(), clip }
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Measures the time between frames and sums it up over a fixed interval.
+pub struct FrameTimer {
+    interval: Duration,
+    last_frame: Instant,
+    delta: Duration,
+    frames: u32,
+    total: Duration,
+    longest: Duration,
+}
+
+/// Frame times over one interval of a `FrameTimer`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FrameStats {
+    pub frames: u32,
+    pub average: Duration,
+    pub longest: Duration,
+}
+
+impl FrameTimer {
+    pub fn new(interval: Duration) -> FrameTimer {
+        FrameTimer {
+            interval,
+            last_frame: Instant::now(),
+            delta: Duration::ZERO,
+            frames: 0,
+            total: Duration::ZERO,
+            longest: Duration::ZERO,
+        }
+    }
+
+    /// Marks the end of a frame, returning the stats for the interval once it has passed.
+    pub fn tick(&mut self) -> Option<FrameStats> {
+        let now = Instant::now();
+        self.delta = now - self.last_frame;
+        self.last_frame = now;
+
+        self.frames += 1;
+        self.total += self.delta;
+        self.longest = self.longest.max(self.delta);
+        if self.total < self.interval {
+            return None;
+        }
+
+        let stats = FrameStats { frames: self.frames, average: self.total / self.frames, longest: self.longest };
+        self.frames = 0;
+        self.total = Duration::ZERO;
+        self.longest = Duration::ZERO;
+        Some(stats)
+    }
+
+    /// Time between the last two ticks.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+}
+
+impl FrameStats {
+    pub fn fps(&self) -> f32 {
+        if self.average.is_zero() {
+            return 0.0;
+        }
+        1.0 / self.average.as_secs_f32()
+    }
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} ms per frame ({:.0} fps), longest {:.2} ms",
+            self.average.as_secs_f64() * 1000.0,
+            self.fps(),
+            self.longest.as_secs_f64() * 1000.0
+        )
+    }
+}
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use super::buffer::{DynamicBuffers, DynamicChunk};
+use super::upload::UploadBatch;
+use super::vertex_layout::VertexLayout;
+
+use crate::geometry::{Rect, Vec2, Vec3};
+
+use vulkano::buffer::immutable::ImmutableBuffer;
+use vulkano::device::Queue;
+
+/// Vertex and index buffers uploaded from a `Mesh`.
+pub type MeshBuffers<V> = (Arc<ImmutableBuffer<[V]>>, Arc<ImmutableBuffer<[u32]>>);
+
+/// Vertices and indices kept on the CPU, uploaded to the GPU the first time they are drawn and again only after
+/// they change.
+///
+/// Any access through `vertices_mut`, `indices_mut` or the editing methods marks the mesh as dirty.
+#[derive(Debug, Clone)]
+pub struct Mesh<V: VertexLayout> {
+    vertices: Vec<V>,
+    indices: Vec<u32>,
+    buffers: Option<MeshBuffers<V>>,
+    dirty: bool,
+}
+
+impl<V: VertexLayout> Default for Mesh<V> {
+    fn default() -> Mesh<V> {
+        Mesh::new(Vec::new(), Vec::new())
+    }
+}
+
+impl<V: VertexLayout> From<(Vec<V>, Vec<u32>)> for Mesh<V> {
+    fn from((vertices, indices): (Vec<V>, Vec<u32>)) -> Mesh<V> {
+        Mesh::new(vertices, indices)
+    }
+}
+
+impl<V: VertexLayout> Mesh<V> {
+    pub fn new(vertices: Vec<V>, indices: Vec<u32>) -> Mesh<V> {
+        Mesh { vertices, indices, buffers: None, dirty: true }
+    }
+
+    /// One mesh holding every mesh in turn.
+    pub fn merged<'a>(meshes: impl IntoIterator<Item = &'a Mesh<V>>) -> Mesh<V> {
+        let mut merged = Mesh::default();
+        for mesh in meshes {
+            merged.merge(mesh);
+        }
+        merged
+    }
+
+    pub fn vertices(&self) -> &[V] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub fn vertices_mut(&mut self) -> &mut Vec<V> {
+        self.dirty = true;
+        &mut self.vertices
+    }
+
+    pub fn indices_mut(&mut self) -> &mut Vec<u32> {
+        self.dirty = true;
+        &mut self.indices
+    }
+
+    pub fn set(&mut self, vertices: Vec<V>, indices: Vec<u32>) {
+        self.vertices = vertices;
+        self.indices = indices;
+        self.dirty = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.set(Vec::new(), Vec::new());
+    }
+
+    /// Adds vertices and the indices drawing them, counted from the first of the new vertices.
+    pub fn append(&mut self, vertices: &[V], indices: &[u32]) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(vertices);
+        self.indices.extend(indices.iter().map(|index| index + offset));
+        self.dirty = true;
+    }
+
+    pub fn merge(&mut self, other: &Mesh<V>) {
+        self.append(&other.vertices, &other.indices);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn index_count(&self) -> u32 {
+        self.indices.len() as u32
+    }
+
+    /// Whether the data has changed since it was last uploaded.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Forces the next call to `buffers` to upload again.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Smallest rect containing the x and y of every vertex.
+    pub fn bounds(&self) -> Option<Rect> {
+        let (min, max) = self.bounds_3d()?;
+        Some(Rect { min: Vec2::new(min.x, min.y), max: Vec2::new(max.x, max.y) })
+    }
+
+    /// Smallest and largest corners of the box containing every vertex.
+    pub fn bounds_3d(&self) -> Option<(Vec3, Vec3)> {
+        let mut positions = self.vertices.iter().map(|vertex| vertex.position());
+        let first = positions.next()?;
+        Some(positions.fold((first, first), |(min, max), position| (min.min(position), max.max(position))))
+    }
+
+    /// GPU copies of the vertices and indices, uploading them first and waiting for the upload if they are missing or
+    /// dirty. Empty meshes have nothing to upload and return `None`.
+    pub fn buffers(&mut self, queue: &Arc<Queue>) -> Option<MeshBuffers<V>> {
+        if self.needs_upload() {
+            let mut batch = UploadBatch::new(queue);
+            self.upload(&mut batch);
+            batch.submit_and_wait();
+        }
+
+        self.buffers.clone()
+    }
+
+    /// Like `buffers`, but records the upload into `batch` instead of waiting for it. The buffers cannot be drawn
+    /// until the batch's future has executed.
+    pub fn upload(&mut self, batch: &mut UploadBatch) -> Option<MeshBuffers<V>> {
+        if self.vertices.is_empty() || self.indices.is_empty() {
+            self.buffers = None;
+            return None;
+        }
+
+        if self.needs_upload() {
+            self.buffers = Some((batch.vertex_buffer(&self.vertices), batch.index_buffer(&self.indices)));
+            self.dirty = false;
+        }
+
+        self.buffers.clone()
+    }
+
+    fn needs_upload(&self) -> bool {
+        !self.vertices.is_empty() && !self.indices.is_empty() && (self.dirty || self.buffers.is_none())
+    }
+
+    /// Copies the current data into per-frame buffers from `pool`, for meshes that change too often to be worth
+    /// caching. Does not touch the cached buffers or the dirty flag.
+    pub fn stream(&self, pool: &DynamicBuffers<V>) -> Option<(DynamicChunk<V>, DynamicChunk<u32>)> {
+        pool.upload(&self.vertices, &self.indices)
+    }
+}
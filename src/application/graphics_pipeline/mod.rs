@@ -1,33 +1,64 @@
+#[allow(dead_code)]
+mod atlas;
+#[allow(dead_code)]
 mod buffer;
-
-mod vertex_shader {
-    vulkano_shaders::shader! {
-        ty: "vertex",
-        path: "src/shaders/shader.vert"
-    }
-}
-
-mod fragment_shader {
-    vulkano_shaders::shader! {
-        ty: "fragment",
-        path: "src/shaders/shader.frag"
-    }
-}
-
-use buffer::{get_command_buffers, get_framebuffers, create_vertex_buffer, create_index_buffer};
-
+#[allow(dead_code)]
+mod camera;
+#[allow(dead_code)]
+mod clip;
+#[allow(dead_code)]
+mod frame_timer;
+#[allow(dead_code)]
+mod mesh;
+#[allow(dead_code)]
+mod render_target;
+#[allow(dead_code)]
+mod sprite;
+#[allow(dead_code)]
+mod texture;
+#[allow(dead_code)]
+mod uniforms;
+#[allow(dead_code)]
+mod upload;
+#[allow(dead_code)]
+mod vertex_layout;
+
+use atlas::{AtlasBuilder, TextureAtlas};
+use buffer::{get_command_buffer, get_command_buffers, get_framebuffers, DrawCall};
+use camera::{Camera2D, Camera3D, FlyController, OrbitController};
+use clip::DrawRegion;
+use frame_timer::FrameTimer;
+use mesh::Mesh;
+use render_target::{DepthTest, RenderTarget};
+use sprite::{Sprite, SpriteBatch, TextureId};
+use texture::{SamplerOptions, Texture};
+use uniforms::{DrawConstants, FrameUniformBuffers, FrameUniforms};
+use upload::UploadBatch;
+use vertex_layout::VertexLayout;
+
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 use super::window_surface::WindowSurface;
 
-use crate::geometry::{Vertex, get_middle_position};
+use crate::geometry::{Colour, ColouredTexturedVertex, Rect, TexturedVertex, Vec2, Vec3, Vertex, Vertex3D};
+
+use image::imageops::FilterType;
+use image::{Rgba, RgbaImage};
 
 use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
 use vulkano::image::SwapchainImage;
+use vulkano::pipeline::graphics::color_blend::ColorBlendState;
 use vulkano::pipeline::graphics::input_assembly::InputAssemblyState;
 use vulkano::pipeline::graphics::vertex_input::BuffersDefinition;
-use vulkano::pipeline::graphics::viewport::{Viewport, ViewportState};
+use vulkano::pipeline::graphics::viewport::ViewportState;
 use vulkano::pipeline::GraphicsPipeline;
-use vulkano::render_pass::{RenderPass, Subpass};
+use vulkano::image::{ImageLayout, SampleCount};
+use vulkano::render_pass::{
+    AttachmentDescription, AttachmentReference, LoadOp, RenderPass, RenderPassCreateInfo, StoreOp, Subpass,
+    SubpassDescription,
+};
 use vulkano::shader::ShaderModule;
 use vulkano::swapchain::{
     AcquireError, Swapchain, SwapchainCreateInfo, SwapchainCreationError, acquire_next_image
@@ -35,102 +66,299 @@ use vulkano::swapchain::{
 use vulkano::sync::{self, FenceSignalFuture, FlushError, GpuFuture};
 
 
-use winit::event::{Event, WindowEvent};
+use winit::event::{ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::Window;
 
-pub fn get_pipeline(device: &Arc<Device>, vertex_shader: &Arc<ShaderModule>, fragment_shader: &Arc<ShaderModule>, render_pass: &Arc<RenderPass>, viewport: &Viewport) -> Arc<GraphicsPipeline> {
+/// Pipeline reading vertices laid out as `V`, which should match the shaders from `V::load_shaders`.
+///
+/// The viewport and scissor are dynamic, so the same pipeline keeps working after a resize and every command buffer
+/// has to set both before drawing. `depth` is ignored when the render pass has no depth attachment. Colours are
+/// blended by their alpha, so transparent texels of a texture let what is behind them show through.
+pub fn get_pipeline<V: VertexLayout>(device: &Arc<Device>, vertex_shader: &Arc<ShaderModule>, fragment_shader: &Arc<ShaderModule>, render_pass: &Arc<RenderPass>, depth: DepthTest) -> Arc<GraphicsPipeline> {
+    let subpass = Subpass::from(render_pass.clone(), 0).unwrap();
+    let depth = if subpass.has_depth() { depth } else { DepthTest::Off };
+
     GraphicsPipeline::start()
-        .vertex_input_state(BuffersDefinition::new().vertex::<Vertex>())
+        .vertex_input_state(BuffersDefinition::new().vertex::<V>())
         .vertex_shader(vertex_shader.entry_point("main").unwrap(), ())
         .input_assembly_state(InputAssemblyState::new())
-        .viewport_state(ViewportState::viewport_fixed_scissor_irrelevant([viewport.clone()]))
+        .viewport_state(ViewportState::viewport_dynamic_scissor_dynamic(1))
         .fragment_shader(fragment_shader.entry_point("main").unwrap(), ())
-        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+        .depth_stencil_state(depth.state())
+        .color_blend_state(ColorBlendState::new(subpass.num_color_attachments()).blend_alpha())
+        .render_pass(subpass)
         .build(device.clone())
         .unwrap()
 }
 
-fn get_render_pass(device: &Arc<Device>, swapchain: &Arc<Swapchain<Window>>) -> Arc<RenderPass> {
-    vulkano::single_pass_renderpass!(
+/// Render pass drawing into the swapchain images as `target` describes. With multisampling the first attachment is
+/// the multisampled image and the second the swapchain image it is resolved into, the depth attachment comes last.
+fn get_render_pass(device: &Arc<Device>, swapchain: &Arc<Swapchain<Window>>, target: &RenderTarget) -> Arc<RenderPass> {
+    let colour = AttachmentDescription {
+        format: Some(swapchain.image_format()),  // set the format the same as the swapchain
+        samples: target.samples,
+        load_op: target.load_op,
+        store_op: target.store_op,
+        initial_layout: ImageLayout::ColorAttachmentOptimal,
+        final_layout: ImageLayout::ColorAttachmentOptimal,
+        ..Default::default()
+    };
+
+    let reference = |attachment: usize, layout| AttachmentReference {
+        attachment: attachment as u32,
+        layout,
+        ..Default::default()
+    };
+
+    let mut attachments = Vec::new();
+    let mut subpass = SubpassDescription::default();
+
+    if target.is_multisampled() {
+        attachments.push(AttachmentDescription {
+            // only kept when the next frame loads it again
+            store_op: if target.load_op == LoadOp::Load { StoreOp::Store } else { StoreOp::DontCare },
+            ..colour
+        });
+        attachments.push(AttachmentDescription {
+            samples: SampleCount::Sample1,
+            load_op: LoadOp::DontCare,
+            ..colour
+        });
+
+        subpass.color_attachments.push(Some(reference(0, ImageLayout::ColorAttachmentOptimal)));
+        subpass.resolve_attachments.push(Some(reference(1, ImageLayout::ColorAttachmentOptimal)));
+    } else {
+        attachments.push(colour);
+        subpass.color_attachments.push(Some(reference(0, ImageLayout::ColorAttachmentOptimal)));
+    }
+
+    if let Some(depth_format) = target.depth_format {
+        subpass.depth_stencil_attachment = Some(reference(attachments.len(), ImageLayout::DepthStencilAttachmentOptimal));
+        attachments.push(AttachmentDescription {
+            format: Some(depth_format),
+            samples: target.samples,
+            // depth is only needed while drawing the frame
+            load_op: LoadOp::Clear,
+            store_op: StoreOp::DontCare,
+            stencil_load_op: LoadOp::Clear,
+            stencil_store_op: StoreOp::DontCare,
+            initial_layout: ImageLayout::DepthStencilAttachmentOptimal,
+            final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+            ..Default::default()
+        });
+    }
+
+    RenderPass::new(
         device.clone(),
-        attachments: {
-            color: {
-                load: Clear,
-                store: Store,
-                format: swapchain.image_format(),  // set the format the same as the swapchain
-                samples: 1,
-            }
+        RenderPassCreateInfo {
+            attachments,
+            subpasses: vec![subpass],
+            ..Default::default()
         },
-        pass: {
-            color: [color],
-            depth_stencil: {}
-        }
     )
     .unwrap()
 }
 
-pub fn finalise(device: Arc<Device>, queue: Arc<Queue>, mut surface: WindowSurface, mut swapchain: Arc<Swapchain<Window>>, images: Vec<Arc<SwapchainImage<Window>>>, event_loop: EventLoop<()>) {
-    let render_pass = get_render_pass(&device, &swapchain);
-    let framebuffers = get_framebuffers(&images, &render_pass);
+/// What the window shows, switched with the number keys.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+enum Scene {
+    /// 1: the square through a `Camera2D`, zoomed with the mouse wheel.
+    #[default]
+    Flat,
+    /// 2: a cube through a `Camera3D`, dragged around with the left mouse button and zoomed with the mouse wheel.
+    Orbit,
+    /// 3: the same cube, flown around with W, A, S, D, space and left shift while dragging to look around.
+    Fly,
+    /// 4: a textured quad through the same `Camera2D` as the square.
+    Textured,
+    /// 5: a grid of spinning sprites from one atlas, batched and uploaded again every frame.
+    Sprites,
+}
 
-    vulkano::impl_vertex!(Vertex, position, colour);
-    
-    let mut size = 0.25;
+impl Scene {
+    fn is_3d(self) -> bool {
+        matches!(self, Scene::Orbit | Scene::Fly)
+    }
+}
 
-    let vertex1 = Vertex {
-        position: [-0.5* size, 0.5* size] ,
-        colour: [255.0, 0.0, 0.0]
-    };
-    
-    let vertex2 = Vertex {
-        position: [0.5* size, 0.5* size],
-        colour: [0.0, 255.0, 0.0]
-    };
-    let vertex3 = Vertex {
-        position: [0.5* size, -0.5* size],
-        colour: [0.0, 0.0, 255.0]
-    };
-    let vertex4 = Vertex {
-        position: [-0.5* size, -0.5* size],
-        colour: [255.0, 255.0, 0.0]
-    };
-    let vertex_buffer = create_vertex_buffer(vec![vertex1, vertex2, vertex3, vertex4], &queue);
-    
-    let index_buffer = create_index_buffer(vec![0, 1, 2, 2, 3, 0], &queue);
+/// Cube centered on the origin with a different colour on every face, for the 3D scenes.
+fn coloured_cube(half_size: f32) -> (Vec<Vertex3D>, Vec<u32>) {
+    let faces = [
+        (Vec3::X, Vec3::Y, Colour::RED),
+        (-Vec3::X, Vec3::Y, Colour::GREEN),
+        (Vec3::Y, Vec3::Z, Colour::BLUE),
+        (-Vec3::Y, Vec3::Z, Colour::YELLOW),
+        (Vec3::Z, Vec3::X, Colour::CYAN),
+        (-Vec3::Z, Vec3::X, Colour::MAGENTA),
+    ];
+
+    let mut vertices = Vec::with_capacity(24);
+    let mut indices = Vec::with_capacity(36);
+    for (normal, up, colour) in faces {
+        let right = up.cross(normal);
+        let first = vertices.len() as u32;
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            vertices.push(Vertex3D::new((normal + right * x + up * y) * half_size, colour));
+        }
+        indices.extend([0, 1, 2, 2, 3, 0].map(|index| first + index));
+    }
 
-    println!("{:?}", get_middle_position(vec![vertex1.position, vertex2.position, vertex3.position, vertex4.position]));
+    (vertices, indices)
+}
 
-    let vertex_shader = vertex_shader::load(device.clone()).expect("failed to create shader module");
-    let fragment_shader = fragment_shader::load(device.clone()).expect("failed to create shader module");
+/// Images for the sprite scene: the picture shrunk down, a checkerboard and a round dot.
+fn sprite_atlas() -> AtlasBuilder {
+    let picture = image::load_from_memory(include_bytes!("../../../image.png")).expect("failed to decode image.png");
 
-    let mut viewport = Viewport {
-        origin: [1.0, 0.0],
-        dimensions: surface.surface.window().inner_size().into(),
-        depth_range: 0.0..1.0,
-    };
+    let mut atlas = AtlasBuilder::new();
+    atlas.add("picture", picture.resize_exact(64, 64, FilterType::Triangle).to_rgba8());
+    atlas.add(
+        "checker",
+        RgbaImage::from_fn(16, 16, |x, y| if (x / 4 + y / 4) % 2 == 0 { Rgba([255; 4]) } else { Rgba([96, 96, 96, 255]) }),
+    );
+    atlas.add(
+        "dot",
+        RgbaImage::from_fn(32, 32, |x, y| {
+            let distance = Vec2::new(x as f32 - 15.5, y as f32 - 15.5).length();
+            Rgba([255, 255, 255, ((16.0 - distance).clamp(0.0, 1.0) * 255.0) as u8])
+        }),
+    );
+    atlas
+}
+
+/// Refills `batch` with a grid of sprites cycling through the atlas, spinning and changing colour over `time`.
+fn push_sprites(batch: &mut SpriteBatch, texture: TextureId, atlas: &TextureAtlas, time: f32) {
+    const SIDE: usize = 16;
+    let names = ["picture", "checker", "dot"];
+    let spacing = 1.8 / SIDE as f32;
+
+    batch.clear();
+    for i in 0..SIDE * SIDE {
+        let cell = Vec2::new((i % SIDE) as f32, (i / SIDE) as f32);
+        let sprite = Sprite::new(Vec2::splat(spacing * 0.5 - 0.9) + cell * spacing, Vec2::splat(spacing * 1.2))
+            .with_rotation(time + i as f32 * 0.1)
+            .with_colour(Colour::from_hsv((i as f32 * 1.4 + time * 30.0) % 360.0, 0.4, 1.0, 1.0))
+            .with_uvs(atlas.uvs(names[i % names.len()]).expect("image is missing from the atlas"))
+            // every other sprite is drawn on top of its neighbours
+            .with_layer((i % 2) as i32);
+        batch.push(texture, sprite);
+    }
+}
+
+/// Right, up and forward movement from the held keys, for `FlyController::update`.
+fn fly_movement(held: &HashSet<VirtualKeyCode>) -> Vec3 {
+    let axis = |positive, negative| held.contains(&positive) as i32 as f32 - held.contains(&negative) as i32 as f32;
+    Vec3::new(
+        axis(VirtualKeyCode::D, VirtualKeyCode::A),
+        axis(VirtualKeyCode::Space, VirtualKeyCode::LShift),
+        axis(VirtualKeyCode::W, VirtualKeyCode::S),
+    )
+}
+
+pub fn finalise(device: Arc<Device>, queue: Arc<Queue>, mut surface: WindowSurface, mut swapchain: Arc<Swapchain<Window>>, mut images: Vec<Arc<SwapchainImage<Window>>>, event_loop: EventLoop<()>) {
+    // can be changed at any time, the loop below picks the changes up before the next frame
+    let mut target = RenderTarget::default().with_clear_colour(Colour::BLACK).with_depth(Format::D16_UNORM);
+    let mut current_target = target;
+
+    let mut render_pass = get_render_pass(&device, &swapchain, &target);
+    let mut framebuffers = get_framebuffers(&images, &render_pass, &target);
+
+
+    let size = 0.25;
+
+    let colours = [Colour::RED, Colour::GREEN, Colour::BLUE, Colour::YELLOW];
+
+    let square = Rect::from_center_size(Vec2::ZERO, Vec2::splat(size));
+    let mut mesh = Mesh::from(square.to_mesh(colours));
+
+    let mut cube = Mesh::from(coloured_cube(0.5));
+    let mut quad = Mesh::from(Rect::from_center_size(Vec2::ZERO, Vec2::ONE).to_textured_mesh(Rect::UNIT));
 
-    let pipeline = get_pipeline(
+    let mut uploads = UploadBatch::new(&queue);
+    mesh.upload(&mut uploads).expect("mesh has no triangles");
+    cube.upload(&mut uploads).expect("cube has no triangles");
+    quad.upload(&mut uploads).expect("quad has no triangles");
+    let texture = Texture::from_memory(include_bytes!("../../../image.png"), &mut uploads).expect("failed to decode image.png");
+    let atlas = sprite_atlas().build(&mut uploads).expect("failed to pack the sprite atlas");
+    let sampler = SamplerOptions::default().create(&device);
+
+    // joined into the first frame, so nothing is drawn before the uploads finish
+    let mut pending_uploads = Some(uploads.submit());
+
+    let (vertex_shader, fragment_shader) = Vertex::load_shaders(&device);
+    let (vertex_shader_3d, fragment_shader_3d) = Vertex3D::load_shaders(&device);
+    let (textured_vertex_shader, textured_fragment_shader) = TexturedVertex::load_shaders(&device);
+    let (sprite_vertex_shader, sprite_fragment_shader) = ColouredTexturedVertex::load_shaders(&device);
+
+    let mut scene = Scene::default();
+    let mut camera = Camera2D::default();
+    let mut cursor = Vec2::ZERO;
+
+    let mut camera_3d = Camera3D::looking_at(Vec3::new(2.0, 1.5, 3.0), Vec3::ZERO);
+    let mut orbit = OrbitController::from_camera(&camera_3d, Vec3::ZERO);
+    let fly = FlyController::default();
+    let mut held_keys = HashSet::new();
+    let mut dragging = false;
+    // cursor movement while dragging, used up by the fly controls every frame
+    let mut mouse_delta = Vec2::ZERO;
+
+    // a smaller region or clip rect only draws part of the window
+    let mut region = camera.region(surface.surface.window().inner_size().into());
+
+    let mut pipeline = get_pipeline::<Vertex>(
         &device,
         &vertex_shader,
         &fragment_shader,
         &render_pass,
-        &viewport,
+        DepthTest::Off,
     );
-
-    let mut command_buffers = get_command_buffers(
+    let mut pipeline_3d = get_pipeline::<Vertex3D>(
+        &device,
+        &vertex_shader_3d,
+        &fragment_shader_3d,
+        &render_pass,
+        DepthTest::ReadWrite,
+    );
+    let mut textured_pipeline = get_pipeline::<TexturedVertex>(
+        &device,
+        &textured_vertex_shader,
+        &textured_fragment_shader,
+        &render_pass,
+        DepthTest::Off,
+    );
+    let mut sprite_pipeline = get_pipeline::<ColouredTexturedVertex>(
         &device,
-        &queue,
-        &pipeline,
-        &framebuffers,
-        &vertex_buffer,
-        &index_buffer,
+        &sprite_vertex_shader,
+        &sprite_fragment_shader,
+        &render_pass,
+        DepthTest::Off,
     );
+    // descriptor sets belong to a pipeline, so these are made again along with it
+    let mut texture_set = texture.descriptor_set(&textured_pipeline, &sampler);
+    let mut sprites = SpriteBatch::new(&device);
+    let atlas_id = sprites.add_texture(atlas.texture().descriptor_set(&sprite_pipeline, &sampler));
+    // seconds since the start, moving the sprites
+    let mut time = 0.0;
+
+    // the uniforms can change every frame, changing the constants means recording the command buffers again. Every
+    // vertex shader declares the same frame uniforms, so the sets bind to either pipeline
+    let constants = DrawConstants::default();
+    let mut uniforms = FrameUniformBuffers::new(&device, &pipeline, images.len());
+
+    // recorded before the first frame
+    let mut command_buffers = Vec::new();
 
     let frames_in_flight = images.len();
     let mut fences: Vec<Option<Arc<FenceSignalFuture<_>>>> = vec![None; frames_in_flight];
     let mut previous_fence_i = 0;
 
+    // set when the command buffers refer to an old swapchain, viewport or scene
+    let mut commands_outdated = true;
+    let mut frame_timer = FrameTimer::new(Duration::from_secs(1));
+    // F prints frame times once a second
+    let mut show_frame_stats = false;
+
     event_loop.run(move |event, _, control_flow| match event {
         Event::WindowEvent {
             event: WindowEvent::CloseRequested,
@@ -138,6 +366,96 @@ pub fn finalise(device: Arc<Device>, queue: Arc<Queue>, mut surface: WindowSurfa
         } => {
             *control_flow = ControlFlow::Exit;
         }
+        Event::WindowEvent {
+            event: WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::M),
+                    ..
+                },
+                ..
+            },
+            ..
+        } => {
+            // toggles 4x multisampling
+            let samples = if target.is_multisampled() { SampleCount::Sample1 } else { SampleCount::Sample4 };
+            if target.with_samples(samples).is_supported(&device) {
+                target.samples = samples;
+            }
+        }
+        Event::WindowEvent {
+            event: WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::F),
+                    ..
+                },
+                ..
+            },
+            ..
+        } => {
+            show_frame_stats = !show_frame_stats;
+        }
+        Event::WindowEvent {
+            event: WindowEvent::KeyboardInput {
+                input: KeyboardInput { state, virtual_keycode: Some(key), .. },
+                ..
+            },
+            ..
+        } => {
+            if state == ElementState::Released {
+                held_keys.remove(&key);
+                return;
+            }
+            held_keys.insert(key);
+
+            let next = match key {
+                VirtualKeyCode::Key1 => Scene::Flat,
+                VirtualKeyCode::Key2 => Scene::Orbit,
+                VirtualKeyCode::Key3 => Scene::Fly,
+                VirtualKeyCode::Key4 => Scene::Textured,
+                VirtualKeyCode::Key5 => Scene::Sprites,
+                _ => return,
+            };
+            if next == Scene::Orbit {
+                // carries on from wherever flying left the camera
+                orbit = OrbitController::from_camera(&camera_3d, orbit.target);
+            }
+            if next != scene {
+                scene = next;
+                commands_outdated = true;
+            }
+        }
+        Event::WindowEvent {
+            event: WindowEvent::CursorMoved { position, .. },
+            ..
+        } => {
+            let position = Vec2::new(position.x as f32, position.y as f32);
+            if dragging {
+                let delta = position - cursor;
+                mouse_delta += delta;
+                if scene == Scene::Orbit {
+                    orbit.rotate(-delta.x * 0.01, -delta.y * 0.01);
+                }
+            }
+            cursor = position;
+        }
+        Event::WindowEvent {
+            event: WindowEvent::MouseInput { state, button: MouseButton::Left, .. },
+            ..
+        } => {
+            dragging = state == ElementState::Pressed;
+        }
+        Event::WindowEvent {
+            event: WindowEvent::MouseWheel { delta: MouseScrollDelta::LineDelta(_, lines), .. },
+            ..
+        } => {
+            match scene {
+                Scene::Flat | Scene::Textured | Scene::Sprites => camera.zoom_at(1.1_f32.powf(lines), cursor, surface.surface.window().inner_size().into()),
+                Scene::Orbit => orbit.zoom(1.1_f32.powf(-lines)),
+                Scene::Fly => {}
+            }
+        }
         Event::WindowEvent {
             event: WindowEvent::Resized(_),
             ..
@@ -145,61 +463,148 @@ pub fn finalise(device: Arc<Device>, queue: Arc<Queue>, mut surface: WindowSurfa
             surface.window_resized = true;
         }
         Event::MainEventsCleared => {
-            let new_dimensions = surface.surface.window().inner_size();
-
-            let (new_swapchain, new_images) = match swapchain.recreate(SwapchainCreateInfo {
-                image_extent: new_dimensions.into(),
-                ..swapchain.create_info()
-            }) {
-                Ok(r) => r,
-                Err(SwapchainCreationError::ImageExtentNotSupported { .. }) => return,
-                Err(e) => panic!("Failed to recreate swapchain: {:?}", e),
-            };
-            swapchain = new_swapchain;
-            let new_framebuffers = get_framebuffers(&new_images, &render_pass);
+            let dimensions = surface.surface.window().inner_size();
+            if dimensions.width == 0 || dimensions.height == 0 {
+                // minimised, there is nothing to draw to
+                return;
+            }
 
-            let new_vertex_buffer = {
-                let vertex1 = Vertex { // top right
-                    position: [-0.5* size, 0.5* size] ,
-                    colour: [255.0, 0.0, 0.0]
-                };
-                let vertex2 = Vertex { // top left
-                    position: [0.5* size, 0.5* size],
-                    colour: [0.0, 255.0, 0.0]
-                };
-                let vertex3 = Vertex {
-                    position: [0.5* size, -0.5* size], // bottom left
-                    colour: [0.0, 0.0, 255.0]
-                };
-                let vertex4 = Vertex {
-                    position: [-0.5* size, -0.5* size], // bottom right
-                    colour: [255.0, 255.0, 0.0]
+            if surface.window_resized || surface.recreate_swapchain {
+                let (new_swapchain, new_images) = match swapchain.recreate(SwapchainCreateInfo {
+                    image_extent: dimensions.into(),
+                    ..swapchain.create_info()
+                }) {
+                    Ok(r) => r,
+                    Err(SwapchainCreationError::ImageExtentNotSupported { .. }) => return,
+                    Err(e) => panic!("Failed to recreate swapchain: {:?}", e),
                 };
-                
-                let new_vertex_buffer = create_vertex_buffer(vec![vertex1, vertex2, vertex3, vertex4], &queue);
-                new_vertex_buffer
-            };
+                swapchain = new_swapchain;
+                images = new_images;
+                framebuffers = get_framebuffers(&images, &render_pass, &target);
+                if uniforms.len() != images.len() {
+                    uniforms = FrameUniformBuffers::new(&device, &pipeline, images.len());
+                }
+                surface.recreate_swapchain = false;
+
+                surface.window_resized = false;
+
+                commands_outdated = true;
+            }
+
+            if target != current_target {
+                if !target.same_render_pass(&current_target) {
+                    render_pass = get_render_pass(&device, &swapchain, &target);
+                    framebuffers = get_framebuffers(&images, &render_pass, &target);
+                    pipeline = get_pipeline::<Vertex>(
+                        &device,
+                        &vertex_shader,
+                        &fragment_shader,
+                        &render_pass,
+                        DepthTest::Off,
+                    );
+                    pipeline_3d = get_pipeline::<Vertex3D>(
+                        &device,
+                        &vertex_shader_3d,
+                        &fragment_shader_3d,
+                        &render_pass,
+                        DepthTest::ReadWrite,
+                    );
+                    textured_pipeline = get_pipeline::<TexturedVertex>(
+                        &device,
+                        &textured_vertex_shader,
+                        &textured_fragment_shader,
+                        &render_pass,
+                        DepthTest::Off,
+                    );
+                    sprite_pipeline = get_pipeline::<ColouredTexturedVertex>(
+                        &device,
+                        &sprite_vertex_shader,
+                        &sprite_fragment_shader,
+                        &render_pass,
+                        DepthTest::Off,
+                    );
+                    texture_set = texture.descriptor_set(&textured_pipeline, &sampler);
+                    sprites.replace_texture(atlas_id, atlas.texture().descriptor_set(&sprite_pipeline, &sampler));
+                    uniforms = FrameUniformBuffers::new(&device, &pipeline, images.len());
+                }
+
+                current_target = target;
+                commands_outdated = true;
+            }
 
-            //size = size + 0.001;
-
-            viewport.dimensions = new_dimensions.into();
-
-            let new_pipeline = get_pipeline(
-                &device,
-                &vertex_shader,
-                &fragment_shader,
-                &render_pass,
-                &viewport
-            );
-            
-            command_buffers = get_command_buffers(
-                &device,
-                &queue,
-                &new_pipeline,
-                &new_framebuffers,
-                &new_vertex_buffer,
-                &index_buffer,
-            );
+            // letterboxing moves the viewport when the window or the camera settings change, 3D scenes fill the window
+            let scene_region = if scene.is_3d() { DrawRegion::full(dimensions.into()) } else { camera.region(dimensions.into()) };
+            if scene_region != region {
+                region = scene_region;
+                commands_outdated = true;
+            }
+
+            let delta = frame_timer.delta().as_secs_f32();
+            camera.update(delta);
+            time += delta;
+            match scene {
+                Scene::Flat | Scene::Textured => {}
+                Scene::Sprites => push_sprites(&mut sprites, atlas_id, &atlas, time),
+                Scene::Orbit => orbit.apply(&mut camera_3d),
+                Scene::Fly => fly.update(&mut camera_3d, fly_movement(&held_keys), mouse_delta, delta),
+            }
+            mouse_delta = Vec2::ZERO;
+
+            if commands_outdated || mesh.is_dirty() || cube.is_dirty() || quad.is_dirty() {
+                command_buffers = match scene {
+                    Scene::Flat => {
+                        let (vertex_buffer, index_buffer) = match mesh.buffers(&queue) {
+                            Some(buffers) => buffers,
+                            None => return,
+                        };
+
+                        get_command_buffers(
+                            &queue,
+                            &pipeline,
+                            &framebuffers,
+                            &target,
+                            &region,
+                            &uniforms,
+                            &[DrawCall::new(vertex_buffer, index_buffer, constants)],
+                        )
+                    }
+                    Scene::Orbit | Scene::Fly => {
+                        let (vertex_buffer, index_buffer) = match cube.buffers(&queue) {
+                            Some(buffers) => buffers,
+                            None => return,
+                        };
+
+                        get_command_buffers(
+                            &queue,
+                            &pipeline_3d,
+                            &framebuffers,
+                            &target,
+                            &region,
+                            &uniforms,
+                            &[DrawCall::new(vertex_buffer, index_buffer, constants)],
+                        )
+                    }
+                    Scene::Textured => {
+                        let (vertex_buffer, index_buffer) = match quad.buffers(&queue) {
+                            Some(buffers) => buffers,
+                            None => return,
+                        };
+
+                        get_command_buffers(
+                            &queue,
+                            &textured_pipeline,
+                            &framebuffers,
+                            &target,
+                            &region,
+                            &uniforms,
+                            &[DrawCall::new(vertex_buffer, index_buffer, constants).with_texture(texture_set.clone())],
+                        )
+                    }
+                    // recorded for the acquired image only, below
+                    Scene::Sprites => Vec::new(),
+                };
+                commands_outdated = false;
+            }
 
             let (image_i, suboptimal, acquire_future) =
                 match acquire_next_image(swapchain.clone(), None) {
@@ -220,6 +625,28 @@ pub fn finalise(device: Arc<Device>, queue: Arc<Queue>, mut surface: WindowSurfa
                 image_fence.wait(None).unwrap();
             }
 
+            // nothing reads this image's uniforms any more
+            let view_projection = if scene.is_3d() {
+                camera_3d.view_projection(dimensions.into())
+            } else {
+                camera.view_projection(dimensions.into())
+            };
+            uniforms.write(image_i, FrameUniforms { view_projection });
+
+            // the sprites are uploaded again every frame, so their commands are only recorded for the image drawn next
+            let command_buffer = match scene {
+                Scene::Sprites => get_command_buffer(
+                    &queue,
+                    &sprite_pipeline,
+                    &framebuffers[image_i],
+                    uniforms.descriptor_set(image_i),
+                    &target,
+                    &region,
+                    &sprites.build(),
+                ),
+                _ => command_buffers[image_i].clone(),
+            };
+
             let previous_future = match fences[previous_fence_i].clone() {
                 // Create a NowFuture
                 None => {
@@ -232,9 +659,14 @@ pub fn finalise(device: Arc<Device>, queue: Arc<Queue>, mut surface: WindowSurfa
                 Some(fence) => fence.boxed(),
             };
 
+            let previous_future = match pending_uploads.take() {
+                Some(uploads) => previous_future.join(uploads).boxed(),
+                None => previous_future,
+            };
+
             let future = previous_future
                 .join(acquire_future)
-                .then_execute(queue.clone(), command_buffers[image_i].clone())
+                .then_execute(queue.clone(), command_buffer)
                 .unwrap()
                 .then_swapchain_present(queue.clone(), swapchain.clone(), image_i)
                 .then_signal_fence_and_flush();
@@ -252,6 +684,12 @@ pub fn finalise(device: Arc<Device>, queue: Arc<Queue>, mut surface: WindowSurfa
             };
 
             previous_fence_i = image_i;
+
+            if let Some(stats) = frame_timer.tick() {
+                if show_frame_stats {
+                    println!("{}", stats);
+                }
+            }
         }
         _ => (),
     });
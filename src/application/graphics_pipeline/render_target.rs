@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use crate::geometry::Colour;
+
+use vulkano::device::Device;
+use vulkano::format::{ClearValue, Format};
+use vulkano::image::SampleCount;
+use vulkano::pipeline::StateMode;
+use vulkano::pipeline::graphics::depth_stencil::{CompareOp, DepthState, DepthStencilState};
+use vulkano::render_pass::{LoadOp, StoreOp};
+
+/// How the colour and depth attachments of each frame are set up, cleared and kept.
+///
+/// Changing the clear colour only needs new command buffers, anything else needs a new render pass, framebuffers and
+/// pipelines (see `same_render_pass`).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderTarget {
+    /// Colour the attachment is filled with when `load_op` is `Clear`.
+    pub clear_colour: Colour,
+    /// What happens to the previous contents of the attachment when the render pass starts.
+    pub load_op: LoadOp,
+    /// Whether the rendered image is written out when the render pass ends, anything but `Store` shows nothing.
+    pub store_op: StoreOp,
+    /// Samples per pixel. Above one, drawing goes to a multisampled image that is resolved into the swapchain image.
+    pub samples: SampleCount,
+    /// Format of the depth attachment, `None` for no depth buffer. `Format::D16_UNORM` is supported by every device.
+    pub depth_format: Option<Format>,
+    /// Depth the depth attachment is cleared to at the start of every frame.
+    pub clear_depth: f32,
+}
+
+/// How a pipeline uses the depth attachment. Pipelines for a render target without one never test depth.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DepthTest {
+    /// Draws everything in submission order, as 2D drawing wants.
+    #[default]
+    Off,
+    /// Hides whatever is behind the depth already written, without writing any, for transparent geometry.
+    Read,
+    /// Hides whatever is behind and records its own depth, for opaque geometry.
+    ReadWrite,
+}
+
+impl Default for RenderTarget {
+    fn default() -> RenderTarget {
+        RenderTarget {
+            clear_colour: Colour::BLACK,
+            load_op: LoadOp::Clear,
+            store_op: StoreOp::Store,
+            samples: SampleCount::Sample1,
+            depth_format: None,
+            clear_depth: 1.0,
+        }
+    }
+}
+
+impl RenderTarget {
+    pub fn with_clear_colour(self, clear_colour: Colour) -> RenderTarget {
+        RenderTarget { clear_colour, ..self }
+    }
+
+    pub fn with_load_op(self, load_op: LoadOp) -> RenderTarget {
+        RenderTarget { load_op, ..self }
+    }
+
+    pub fn with_store_op(self, store_op: StoreOp) -> RenderTarget {
+        RenderTarget { store_op, ..self }
+    }
+
+    pub fn with_samples(self, samples: SampleCount) -> RenderTarget {
+        RenderTarget { samples, ..self }
+    }
+
+    pub fn with_depth(self, depth_format: Format) -> RenderTarget {
+        RenderTarget { depth_format: Some(depth_format), ..self }
+    }
+
+    pub fn without_depth(self) -> RenderTarget {
+        RenderTarget { depth_format: None, ..self }
+    }
+
+    pub fn has_depth(&self) -> bool {
+        self.depth_format.is_some()
+    }
+
+    pub fn is_multisampled(&self) -> bool {
+        self.samples != SampleCount::Sample1
+    }
+
+    /// Whether the device can render colour with this many samples.
+    pub fn is_supported(&self, device: &Arc<Device>) -> bool {
+        let counts = device.physical_device().properties().framebuffer_color_sample_counts;
+        match self.samples {
+            SampleCount::Sample1 => counts.sample1,
+            SampleCount::Sample2 => counts.sample2,
+            SampleCount::Sample4 => counts.sample4,
+            SampleCount::Sample8 => counts.sample8,
+            SampleCount::Sample16 => counts.sample16,
+            SampleCount::Sample32 => counts.sample32,
+            SampleCount::Sample64 => counts.sample64,
+        }
+    }
+
+    /// Whether a render pass made for `other` can also be used for this target.
+    pub fn same_render_pass(&self, other: &RenderTarget) -> bool {
+        self.load_op == other.load_op
+            && self.store_op == other.store_op
+            && self.samples == other.samples
+            && self.depth_format == other.depth_format
+    }
+
+    /// One value per attachment of the render pass from `get_render_pass`, in order: colour, resolve, depth.
+    pub fn clear_values(&self) -> Vec<ClearValue> {
+        let colour = match self.load_op {
+            LoadOp::Clear => ClearValue::Float(self.clear_colour.to_array()),
+            _ => ClearValue::None,
+        };
+
+        let mut values = vec![colour];
+        if self.is_multisampled() {
+            // the resolved swapchain image is overwritten, never cleared
+            values.push(ClearValue::None);
+        }
+        if self.has_depth() {
+            values.push(ClearValue::Depth(self.clear_depth));
+        }
+        values
+    }
+}
+
+impl DepthTest {
+    pub fn state(self) -> DepthStencilState {
+        let write_enable = match self {
+            DepthTest::Off => return DepthStencilState::disabled(),
+            DepthTest::Read => false,
+            DepthTest::ReadWrite => true,
+        };
+
+        DepthStencilState {
+            depth: Some(DepthState {
+                enable_dynamic: false,
+                compare_op: StateMode::Fixed(CompareOp::Less),
+                write_enable: StateMode::Fixed(write_enable),
+            }),
+            ..DepthStencilState::disabled()
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::buffer::{DrawCall, DynamicBuffers};
+use super::uniforms::DrawConstants;
+
+use crate::geometry::{Colour, ColouredTexturedVertex, Rect, Vec2, QUAD_INDICES};
+
+use vulkano::buffer::cpu_pool::CpuBufferPoolChunk;
+use vulkano::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::Device;
+use vulkano::memory::pool::StdMemoryPool;
+
+/// Draw of all sprites sharing a texture and layer, as returned by `SpriteBatch::build`.
+pub type SpriteDraw = DrawCall<
+    CpuBufferPoolChunk<ColouredTexturedVertex, Arc<StdMemoryPool>>,
+    CpuBufferPoolChunk<u32, Arc<StdMemoryPool>>,
+>;
+
+/// Textured, coloured and rotated quad in world space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sprite {
+    /// Centre of the quad, which it is rotated around.
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Counter-clockwise, in radians.
+    pub rotation: f32,
+    /// Multiplied with the texture.
+    pub colour: Colour,
+    /// Part of the texture shown, such as `TextureAtlas::uvs`.
+    pub uvs: Rect,
+    /// Sprites on higher layers are drawn over lower ones.
+    pub layer: i32,
+}
+
+impl Sprite {
+    pub fn new(position: Vec2, size: Vec2) -> Sprite {
+        Sprite { position, size, rotation: 0.0, colour: Colour::WHITE, uvs: Rect::UNIT, layer: 0 }
+    }
+
+    pub fn with_rotation(self, rotation: f32) -> Sprite {
+        Sprite { rotation, ..self }
+    }
+
+    pub fn with_colour(self, colour: Colour) -> Sprite {
+        Sprite { colour, ..self }
+    }
+
+    pub fn with_uvs(self, uvs: Rect) -> Sprite {
+        Sprite { uvs, ..self }
+    }
+
+    pub fn with_layer(self, layer: i32) -> Sprite {
+        Sprite { layer, ..self }
+    }
+
+    /// Corners in the order of `Rect::corners`.
+    pub fn corners(self) -> [Vec2; 4] {
+        let half = self.size * 0.5;
+        Rect::new(-half, half).corners().map(|corner| self.position + corner.rotate(self.rotation))
+    }
+
+    pub fn to_vertices(self) -> [ColouredTexturedVertex; 4] {
+        let corners = self.corners();
+        let uv_corners = Rect::uv_corners(self.uvs);
+        [0, 1, 2, 3].map(|i| ColouredTexturedVertex::new(corners[i], self.colour, uv_corners[i]))
+    }
+}
+
+/// Texture registered with `SpriteBatch::add_texture`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextureId(usize);
+
+/// Indices of consecutive sprites with the same texture, drawn at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteRun {
+    pub texture: TextureId,
+    pub indices: Range<u32>,
+}
+
+/// Collects the sprites of a frame and draws them with as few draw calls as possible.
+///
+/// Sprites are sorted by layer and then texture, keeping the order they were pushed in otherwise, so every texture
+/// costs one draw per layer it is used on. Sprites on the same layer with different textures can end up in either
+/// order, overlapping sprites should use separate layers or share an atlas.
+///
+/// All vertices go into one `DynamicBuffers` upload per frame, to be drawn with the coloured textured pipeline.
+pub struct SpriteBatch {
+    buffers: DynamicBuffers<ColouredTexturedVertex>,
+    textures: Vec<Arc<PersistentDescriptorSet>>,
+    sprites: Vec<(TextureId, Sprite)>,
+    vertices: Vec<ColouredTexturedVertex>,
+    indices: Vec<u32>,
+}
+
+impl SpriteBatch {
+    pub fn new(device: &Arc<Device>) -> SpriteBatch {
+        SpriteBatch {
+            buffers: DynamicBuffers::new(device),
+            textures: Vec::new(),
+            sprites: Vec::new(),
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    /// Allocates room for this many sprites, so the first frames do not have to grow the buffers.
+    pub fn reserve(&mut self, sprites: usize) {
+        self.sprites.reserve(sprites);
+        self.vertices.reserve(sprites * 4);
+        self.indices.reserve(sprites * 6);
+        self.buffers.reserve(sprites * 4, sprites * 6);
+    }
+
+    /// Registers a texture from `Texture::descriptor_set` for sprites to use.
+    pub fn add_texture(&mut self, texture: Arc<PersistentDescriptorSet>) -> TextureId {
+        self.textures.push(texture);
+        TextureId(self.textures.len() - 1)
+    }
+
+    /// Points an existing id at another texture, such as after the pipeline was recreated.
+    pub fn replace_texture(&mut self, id: TextureId, texture: Arc<PersistentDescriptorSet>) {
+        self.textures[id.0] = texture;
+    }
+
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    pub fn push(&mut self, texture: TextureId, sprite: Sprite) {
+        assert!(texture.0 < self.textures.len(), "texture was not added to this batch");
+        self.sprites.push((texture, sprite));
+    }
+
+    /// Removes the sprites, keeping the textures and the memory for the next frame.
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+
+    /// Sorts the sprites and turns them into quads, returning the vertices, indices and runs to draw them with.
+    pub fn tessellate(&mut self) -> (&[ColouredTexturedVertex], &[u32], Vec<SpriteRun>) {
+        let runs = tessellate(&mut self.sprites, &mut self.vertices, &mut self.indices);
+        (&self.vertices, &self.indices, runs)
+    }
+
+    /// Uploads the sprites and returns the draws for `get_command_buffer`, all sharing one vertex and index buffer.
+    /// The sprites stay in the batch until `clear`.
+    pub fn build(&mut self) -> Vec<SpriteDraw> {
+        let (_, _, runs) = self.tessellate();
+
+        let (vertex_buffer, index_buffer) = match self.buffers.upload(&self.vertices, &self.indices) {
+            Some(chunks) => chunks,
+            None => return Vec::new(),
+        };
+
+        runs.into_iter()
+            .map(|run| {
+                DrawCall::new(vertex_buffer.clone(), index_buffer.clone(), DrawConstants::default())
+                    .with_texture(self.textures[run.texture.0].clone())
+                    .with_indices(run.indices)
+            })
+            .collect()
+    }
+}
+
+/// Sorts `sprites` by layer and texture and replaces the contents of `vertices` and `indices` with their quads.
+fn tessellate(sprites: &mut [(TextureId, Sprite)], vertices: &mut Vec<ColouredTexturedVertex>, indices: &mut Vec<u32>) -> Vec<SpriteRun> {
+    // stable, so sprites sharing a layer and texture stay in the order they were pushed
+    sprites.sort_by_key(|(texture, sprite)| (sprite.layer, *texture));
+
+    vertices.clear();
+    indices.clear();
+    let mut runs: Vec<SpriteRun> = Vec::new();
+
+    for (i, (texture, sprite)) in sprites.iter().enumerate() {
+        let first_vertex = (i * 4) as u32;
+        vertices.extend_from_slice(&sprite.to_vertices());
+        indices.extend(QUAD_INDICES.iter().map(|index| first_vertex + index));
+
+        let end = indices.len() as u32;
+        match runs.last_mut() {
+            Some(run) if run.texture == *texture => run.indices.end = end,
+            _ => runs.push(SpriteRun { texture: *texture, indices: end - 6..end }),
+        }
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f32::consts::FRAC_PI_2;
+
+    fn sprite_at(x: f32, layer: i32) -> Sprite {
+        Sprite::new(Vec2::new(x, 0.0), Vec2::ONE).with_layer(layer)
+    }
+
+    #[test]
+    fn rotated_corners() {
+        let sprite = Sprite::new(Vec2::new(1.0, 2.0), Vec2::new(4.0, 2.0)).with_rotation(FRAC_PI_2);
+        let expected = Rect::from_center_size(Vec2::new(1.0, 2.0), Vec2::new(2.0, 4.0));
+
+        for corner in sprite.corners() {
+            assert!(expected.corners().iter().any(|point| (*point - corner).length() < 1e-5), "{:?}", corner);
+        }
+    }
+
+    #[test]
+    fn runs_merge_by_layer_and_texture() {
+        let (a, b) = (TextureId(0), TextureId(1));
+        let mut sprites = vec![
+            (b, sprite_at(0.0, 0)),
+            (a, sprite_at(1.0, 1)),
+            (a, sprite_at(2.0, 0)),
+            (b, sprite_at(3.0, 0)),
+            (a, sprite_at(4.0, 1)),
+        ];
+        let (mut vertices, mut indices) = (Vec::new(), Vec::new());
+        let runs = tessellate(&mut sprites, &mut vertices, &mut indices);
+
+        assert_eq!(
+            runs,
+            vec![
+                SpriteRun { texture: a, indices: 0..6 },
+                SpriteRun { texture: b, indices: 6..18 },
+                SpriteRun { texture: a, indices: 18..30 },
+            ]
+        );
+        assert_eq!((vertices.len(), indices.len()), (20, 30));
+
+        // pushed order is kept within a run
+        let xs: Vec<f32> = sprites.iter().map(|(_, sprite)| sprite.position.x).collect();
+        assert_eq!(xs, vec![2.0, 0.0, 3.0, 1.0, 4.0]);
+        assert!(indices.iter().all(|index| (*index as usize) < vertices.len()));
+        assert_eq!(&indices[6..12], &QUAD_INDICES.map(|index| index + 4));
+    }
+
+    #[test]
+    fn tessellating_again_replaces_the_quads() {
+        let mut sprites = vec![(TextureId(0), sprite_at(0.0, 0))];
+        let (mut vertices, mut indices) = (Vec::new(), Vec::new());
+        tessellate(&mut sprites, &mut vertices, &mut indices);
+        let runs = tessellate(&mut sprites, &mut vertices, &mut indices);
+
+        assert_eq!(runs.len(), 1);
+        assert_eq!((vertices.len(), indices.len()), (4, 6));
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use super::upload::UploadBatch;
+
+use image::{DynamicImage, ImageError};
+
+use vulkano::descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet};
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::image::{ImmutableImage, MipmapsCount};
+use vulkano::image::view::ImageView;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline};
+use vulkano::sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode, LOD_CLAMP_NONE};
+
+/// Descriptor set the textured shaders read their `sampler2D` from, binding 0 of it. Set 0 holds the frame uniforms.
+pub const TEXTURE_SET: u32 = 1;
+
+/// Image on the GPU that shaders can sample, uploaded with mipmaps through an `UploadBatch`.
+///
+/// Pixels are stored as sRGB, which is what PNG and JPEG files hold, so sampling returns linear colours.
+#[derive(Debug, Clone)]
+pub struct Texture {
+    view: Arc<ImageView<ImmutableImage>>,
+    dimensions: [u32; 2],
+}
+
+impl Texture {
+    /// Decodes a PNG, JPEG or any other format the `image` crate knows, guessed from the file's contents.
+    pub fn load<P: AsRef<Path>>(file: P, batch: &mut UploadBatch) -> Result<Texture, ImageError> {
+        Ok(Texture::from_image(&image::open(file)?, batch))
+    }
+
+    /// Like `load`, for a file already in memory, such as one embedded with `include_bytes!`.
+    pub fn from_memory(bytes: &[u8], batch: &mut UploadBatch) -> Result<Texture, ImageError> {
+        Ok(Texture::from_image(&image::load_from_memory(bytes)?, batch))
+    }
+
+    pub fn from_image(image: &DynamicImage, batch: &mut UploadBatch) -> Texture {
+        let rgba = image.to_rgba8();
+        Texture::from_rgba8(rgba.as_raw(), rgba.dimensions().into(), batch)
+    }
+
+    /// Texture from tightly packed rows of 8-bit sRGB red, green, blue and alpha, starting at the top.
+    pub fn from_rgba8(pixels: &[u8], dimensions: [u32; 2], batch: &mut UploadBatch) -> Texture {
+        Texture::from_rgba8_with_mipmaps(pixels, dimensions, MipmapsCount::Log2, batch)
+    }
+
+    /// Like `from_rgba8` with a chosen number of mip levels, for images such as atlases whose smaller levels would
+    /// blend separate parts together.
+    pub fn from_rgba8_with_mipmaps(pixels: &[u8], dimensions: [u32; 2], mipmaps: MipmapsCount, batch: &mut UploadBatch) -> Texture {
+        assert_eq!(pixels.len(), dimensions[0] as usize * dimensions[1] as usize * 4, "pixels do not match the dimensions");
+
+        let image = batch.image_with_mipmaps(pixels, dimensions, Format::R8G8B8A8_SRGB, mipmaps);
+        let view = ImageView::new_default(image).expect("failed to create image view");
+        Texture { view, dimensions }
+    }
+
+    pub fn view(&self) -> &Arc<ImageView<ImmutableImage>> {
+        &self.view
+    }
+
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.dimensions
+    }
+
+    pub fn width(&self) -> u32 {
+        self.dimensions[0]
+    }
+
+    pub fn height(&self) -> u32 {
+        self.dimensions[1]
+    }
+
+    /// Binds the texture with `sampler` for `pipeline`, ready for `DrawCall::with_texture`.
+    pub fn descriptor_set(&self, pipeline: &Arc<GraphicsPipeline>, sampler: &Arc<Sampler>) -> Arc<PersistentDescriptorSet> {
+        let layout = pipeline
+            .layout()
+            .set_layouts()
+            .get(TEXTURE_SET as usize)
+            .expect("pipeline does not sample a texture")
+            .clone();
+
+        PersistentDescriptorSet::new(layout, [WriteDescriptorSet::image_view_sampler(0, self.view.clone(), sampler.clone())])
+            .expect("failed to create descriptor set")
+    }
+}
+
+/// How a texture is filtered and what lies outside of its edges.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SamplerOptions {
+    /// Filter for texels bigger than a pixel.
+    pub mag_filter: Filter,
+    /// Filter for texels smaller than a pixel.
+    pub min_filter: Filter,
+    /// How neighbouring mipmaps are blended.
+    pub mipmap_mode: SamplerMipmapMode,
+    /// Used for both directions.
+    pub wrap: SamplerAddressMode,
+    /// Largest anisotropic filtering ratio, which needs the `sampler_anisotropy` device feature.
+    pub anisotropy: Option<f32>,
+}
+
+impl Default for SamplerOptions {
+    fn default() -> SamplerOptions {
+        SamplerOptions::linear()
+    }
+}
+
+impl SamplerOptions {
+    /// Smooth filtering between texels and mipmaps, repeating the texture.
+    pub fn linear() -> SamplerOptions {
+        SamplerOptions {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            mipmap_mode: SamplerMipmapMode::Linear,
+            wrap: SamplerAddressMode::Repeat,
+            anisotropy: None,
+        }
+    }
+
+    /// Sharp texels for pixel art, clamped at the edges so sprites do not bleed into each other.
+    pub fn nearest() -> SamplerOptions {
+        SamplerOptions {
+            mag_filter: Filter::Nearest,
+            min_filter: Filter::Nearest,
+            mipmap_mode: SamplerMipmapMode::Nearest,
+            wrap: SamplerAddressMode::ClampToEdge,
+            anisotropy: None,
+        }
+    }
+
+    pub fn with_wrap(self, wrap: SamplerAddressMode) -> SamplerOptions {
+        SamplerOptions { wrap, ..self }
+    }
+
+    pub fn with_anisotropy(self, anisotropy: f32) -> SamplerOptions {
+        SamplerOptions { anisotropy: Some(anisotropy), ..self }
+    }
+
+    pub fn create(&self, device: &Arc<Device>) -> Arc<Sampler> {
+        Sampler::new(
+            device.clone(),
+            SamplerCreateInfo {
+                mag_filter: self.mag_filter,
+                min_filter: self.min_filter,
+                mipmap_mode: self.mipmap_mode,
+                address_mode: [self.wrap; 3],
+                anisotropy: self.anisotropy,
+                lod: 0.0..=LOD_CLAMP_NONE,
+                ..Default::default()
+            },
+        )
+        .expect("failed to create sampler")
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::geometry::{Colour, Mat4, Transform2D};
+
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet};
+use vulkano::device::Device;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline};
+
+/// Data shared by every draw in a frame, the `Frame` uniform block at set 0, binding 0 of the vertex shaders.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct FrameUniforms {
+    pub view_projection: Mat4,
+}
+
+impl Default for FrameUniforms {
+    fn default() -> FrameUniforms {
+        FrameUniforms { view_projection: Mat4::IDENTITY }
+    }
+}
+
+/// Data for a single draw, the `Draw` push constant block of the vertex shaders.
+///
+/// Push constants are recorded into the command buffer, so changing them means recording it again. Anything that
+/// changes every frame without new draws belongs in `FrameUniforms`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct DrawConstants {
+    pub model: Mat4,
+    /// Multiplied with the colour of every vertex.
+    pub tint: Colour,
+}
+
+impl Default for DrawConstants {
+    fn default() -> DrawConstants {
+        DrawConstants { model: Mat4::IDENTITY, tint: Colour::WHITE }
+    }
+}
+
+impl DrawConstants {
+    pub fn new(model: Mat4, tint: Colour) -> DrawConstants {
+        DrawConstants { model, tint }
+    }
+
+    pub fn from_transform(transform: Transform2D) -> DrawConstants {
+        DrawConstants { model: transform.to_mat4(), ..DrawConstants::default() }
+    }
+
+    pub fn with_model(self, model: Mat4) -> DrawConstants {
+        DrawConstants { model, ..self }
+    }
+
+    pub fn with_tint(self, tint: Colour) -> DrawConstants {
+        DrawConstants { tint, ..self }
+    }
+}
+
+/// One `FrameUniforms` buffer and descriptor set per swapchain image.
+///
+/// A frame's buffer is only read by the command buffer drawing to the same image, so it can be rewritten as soon as
+/// that image's previous frame has finished, without waiting for the other frames in flight.
+pub struct FrameUniformBuffers {
+    buffers: Vec<Arc<CpuAccessibleBuffer<FrameUniforms>>>,
+    sets: Vec<Arc<PersistentDescriptorSet>>,
+}
+
+impl FrameUniformBuffers {
+    /// Buffers for `count` images, bound through the first descriptor set layout of `pipeline`.
+    pub fn new(device: &Arc<Device>, pipeline: &Arc<GraphicsPipeline>, count: usize) -> FrameUniformBuffers {
+        let layout = pipeline.layout().set_layouts().first().expect("pipeline has no descriptor sets").clone();
+
+        let buffers: Vec<_> = (0..count)
+            .map(|_| {
+                CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::uniform_buffer(), false, FrameUniforms::default())
+                    .expect("failed to create uniform buffer")
+            })
+            .collect();
+
+        let sets = buffers
+            .iter()
+            .map(|buffer| {
+                PersistentDescriptorSet::new(layout.clone(), [WriteDescriptorSet::buffer(0, buffer.clone())])
+                    .expect("failed to create descriptor set")
+            })
+            .collect();
+
+        FrameUniformBuffers { buffers, sets }
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    pub fn descriptor_set(&self, image_i: usize) -> &Arc<PersistentDescriptorSet> {
+        &self.sets[image_i]
+    }
+
+    /// Replaces the uniforms of one image. The last frame drawn to that image has to have finished, or this panics.
+    pub fn write(&self, image_i: usize, uniforms: FrameUniforms) {
+        *self.buffers[image_i].write().expect("uniform buffer is still in use") = uniforms;
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use bytemuck::Pod;
+
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::buffer::immutable::ImmutableBuffer;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer};
+use vulkano::device::Queue;
+use vulkano::format::Format;
+use vulkano::image::{ImageCreateFlags, ImageDimensions, ImageLayout, ImageUsage, ImmutableImage, MipmapsCount};
+use vulkano::sync::{self, GpuFuture};
+
+/// Records many buffer and image uploads into a single command buffer.
+///
+/// Every upload returns its GPU object straight away, but the data only arrives once the future from `submit` has
+/// executed, so that future has to be joined with (or waited on before) the first draw that uses them.
+pub struct UploadBatch {
+    queue: Arc<Queue>,
+    builder: AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    uploads: usize,
+    // uploads vulkano records into command buffers of their own
+    separate: Vec<Box<dyn GpuFuture>>,
+}
+
+impl UploadBatch {
+    pub fn new(queue: &Arc<Queue>) -> UploadBatch {
+        let builder = AutoCommandBufferBuilder::primary(
+            queue.device().clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        UploadBatch { queue: queue.clone(), builder, uploads: 0, separate: Vec::new() }
+    }
+
+    /// Number of uploads recorded so far.
+    pub fn len(&self) -> usize {
+        self.uploads + self.separate.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Device local buffer filled from `data` through a staging buffer. `transfer_destination` is added to the usage.
+    pub fn buffer<T: Pod + Send + Sync + 'static>(&mut self, data: &[T], usage: BufferUsage) -> Arc<ImmutableBuffer<[T]>> {
+        let device = self.queue.device().clone();
+        let staging = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::transfer_source(), false, data.iter().cloned())
+            .expect("failed to create staging buffer");
+
+        let usage = BufferUsage { transfer_destination: true, ..usage };
+        let (buffer, initialisation) = unsafe {
+            // the copy below fills the whole buffer before anything can read from it
+            ImmutableBuffer::uninitialized_array(device, data.len() as u64, usage).expect("failed to create buffer")
+        };
+
+        self.builder.copy_buffer(staging, initialisation).unwrap();
+        self.uploads += 1;
+        buffer
+    }
+
+    pub fn vertex_buffer<V: Pod + Send + Sync + 'static>(&mut self, vertices: &[V]) -> Arc<ImmutableBuffer<[V]>> {
+        self.buffer(vertices, BufferUsage::vertex_buffer())
+    }
+
+    pub fn index_buffer(&mut self, indices: &[u32]) -> Arc<ImmutableBuffer<[u32]>> {
+        self.buffer(indices, BufferUsage::index_buffer())
+    }
+
+    /// Sampled 2D image filled from tightly packed pixels in `format`.
+    pub fn image(&mut self, pixels: &[u8], dimensions: [u32; 2], format: Format) -> Arc<ImmutableImage> {
+        let device = self.queue.device().clone();
+        let staging = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::transfer_source(), false, pixels.iter().cloned())
+            .expect("failed to create staging buffer");
+
+        let (image, initialisation) = ImmutableImage::uninitialized(
+            device.clone(),
+            ImageDimensions::Dim2d { width: dimensions[0], height: dimensions[1], array_layers: 1 },
+            format,
+            MipmapsCount::One,
+            ImageUsage { transfer_destination: true, sampled: true, ..ImageUsage::none() },
+            ImageCreateFlags::none(),
+            ImageLayout::ShaderReadOnlyOptimal,
+            device.active_queue_families(),
+        )
+        .expect("failed to create image");
+
+        self.builder.copy_buffer_to_image(staging, initialisation).unwrap();
+        self.uploads += 1;
+        image
+    }
+
+    /// Sampled 2D image like `image`, with mipmaps generated from the pixels. `MipmapsCount::Log2` makes a full chain.
+    ///
+    /// vulkano only generates mipmaps in a command buffer of its own, which is submitted together with the rest of the
+    /// batch. `format` has to support linear blits, which every colour format used for textures does.
+    pub fn image_with_mipmaps(&mut self, pixels: &[u8], dimensions: [u32; 2], format: Format, mipmaps: MipmapsCount) -> Arc<ImmutableImage> {
+        let device = self.queue.device().clone();
+        let staging = CpuAccessibleBuffer::from_iter(device, BufferUsage::transfer_source(), false, pixels.iter().cloned())
+            .expect("failed to create staging buffer");
+
+        let (image, future) = ImmutableImage::from_buffer(
+            staging,
+            ImageDimensions::Dim2d { width: dimensions[0], height: dimensions[1], array_layers: 1 },
+            mipmaps,
+            format,
+            self.queue.clone(),
+        )
+        .expect("failed to create image");
+
+        self.separate.push(future.boxed());
+        image
+    }
+
+    /// Sends every recorded upload to the queue in one go. The future is not flushed, so it can still be joined with
+    /// the first frame's future.
+    pub fn submit(self) -> Box<dyn GpuFuture> {
+        let mut future = sync::now(self.queue.device().clone()).boxed();
+        for separate in self.separate {
+            future = future.join(separate).boxed();
+        }
+
+        if self.uploads == 0 {
+            return future;
+        }
+
+        let command_buffer = self.builder.build().unwrap();
+        future.then_execute(self.queue, command_buffer).unwrap().boxed()
+    }
+
+    /// Submits the uploads and blocks until they have finished.
+    pub fn submit_and_wait(self) {
+        if self.is_empty() {
+            return;
+        }
+
+        self.submit().then_signal_fence_and_flush().unwrap().wait(None).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use crate::geometry::{ColouredTexturedVertex, NormalVertex, TexturedVertex, Vec3, Vertex, Vertex3D};
+
+use bytemuck::Pod;
+
+use vulkano::device::Device;
+use vulkano::pipeline::graphics::vertex_input::Vertex as VertexInput;
+use vulkano::shader::ShaderModule;
+
+/// A vertex type together with the shaders that read it, so pipelines and buffers can be built for any of them.
+///
+/// The shader inputs are matched to the vertex fields by name.
+pub trait VertexLayout: VertexInput + Pod + Send + Sync + 'static {
+    /// Vertex and fragment shaders for this layout.
+    fn load_shaders(device: &Arc<Device>) -> (Arc<ShaderModule>, Arc<ShaderModule>);
+
+    /// Position of the vertex, with a z of zero for 2D layouts.
+    fn position(&self) -> Vec3;
+}
+
+mod colour_vertex_shader {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/shader.vert"
+    }
+}
+
+mod colour_fragment_shader {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/shader.frag"
+    }
+}
+
+mod textured_vertex_shader {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/textured.vert"
+    }
+}
+
+mod textured_fragment_shader {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/textured.frag"
+    }
+}
+
+mod coloured_textured_vertex_shader {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/coloured_textured.vert"
+    }
+}
+
+mod coloured_textured_fragment_shader {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/coloured_textured.frag"
+    }
+}
+
+mod coloured_3d_vertex_shader {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/coloured_3d.vert"
+    }
+}
+
+mod lit_vertex_shader {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/lit.vert"
+    }
+}
+
+mod lit_fragment_shader {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/lit.frag"
+    }
+}
+
+// pairs a vertex type with the `load` functions of its shader modules
+macro_rules! impl_vertex_layout {
+    ($vertex:ty, $vertex_shader:ident, $fragment_shader:ident, |$this:ident| $position:expr) => {
+        impl VertexLayout for $vertex {
+            fn load_shaders(device: &Arc<Device>) -> (Arc<ShaderModule>, Arc<ShaderModule>) {
+                (
+                    $vertex_shader::load(device.clone()).expect("failed to create shader module"),
+                    $fragment_shader::load(device.clone()).expect("failed to create shader module"),
+                )
+            }
+
+            fn position(&self) -> Vec3 {
+                let $this = self;
+                $position
+            }
+        }
+    };
+}
+
+impl_vertex_layout!(Vertex, colour_vertex_shader, colour_fragment_shader, |vertex| vertex.position.extend(0.0));
+impl_vertex_layout!(TexturedVertex, textured_vertex_shader, textured_fragment_shader, |vertex| vertex.position.extend(0.0));
+impl_vertex_layout!(ColouredTexturedVertex, coloured_textured_vertex_shader, coloured_textured_fragment_shader, |vertex| {
+    vertex.position.extend(0.0)
+});
+impl_vertex_layout!(Vertex3D, coloured_3d_vertex_shader, colour_fragment_shader, |vertex| vertex.position);
+impl_vertex_layout!(NormalVertex, lit_vertex_shader, lit_fragment_shader, |vertex| vertex.position);
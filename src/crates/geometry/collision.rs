@@ -0,0 +1,509 @@
+use super::polygon::{self, Winding};
+use super::rect::Rect;
+use super::vector::{Vec2, EPSILON};
+
+pub use super::polygon::contains_point as point_in_polygon;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl Circle {
+    pub fn new(center: Vec2, radius: f32) -> Circle {
+        Circle { center, radius }
+    }
+
+    pub fn contains_point(self, point: Vec2) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    pub fn bounding_box(self) -> Rect {
+        Rect::from_center_size(self.center, Vec2::splat(self.radius * 2.0))
+    }
+}
+
+/// How two overlapping shapes touch.
+///
+/// `normal` points from the first shape towards the second, so moving the second shape by `normal * depth`
+/// separates them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact {
+    pub normal: Vec2,
+    pub depth: f32,
+    pub points: Vec<Vec2>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: Vec2,
+    pub direction: Vec2,
+}
+
+/// Where a ray first meets a shape, with the surface normal facing back along the ray.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RayHit {
+    pub point: Vec2,
+    pub normal: Vec2,
+    pub distance: f32,
+}
+
+pub fn aabb_vs_aabb(a: Rect, b: Rect) -> Option<Contact> {
+    let overlap = a.max.min(b.max) - a.min.max(b.min);
+    if overlap.x < 0.0 || overlap.y < 0.0 {
+        return None;
+    }
+
+    let offset = b.center() - a.center();
+    let (normal, depth, points) = if overlap.x < overlap.y {
+        let normal = Vec2::new(if offset.x < 0.0 { -1.0 } else { 1.0 }, 0.0);
+        let x = if offset.x < 0.0 { b.max.x } else { b.min.x };
+        (normal, overlap.x, vec![Vec2::new(x, a.min.y.max(b.min.y)), Vec2::new(x, a.max.y.min(b.max.y))])
+    } else {
+        let normal = Vec2::new(0.0, if offset.y < 0.0 { -1.0 } else { 1.0 });
+        let y = if offset.y < 0.0 { b.max.y } else { b.min.y };
+        (normal, overlap.y, vec![Vec2::new(a.min.x.max(b.min.x), y), Vec2::new(a.max.x.min(b.max.x), y)])
+    };
+
+    Some(Contact { normal, depth, points })
+}
+
+pub fn circle_vs_circle(a: Circle, b: Circle) -> Option<Contact> {
+    let offset = b.center - a.center;
+    let distance = offset.length();
+    let radii = a.radius + b.radius;
+    if distance > radii {
+        return None;
+    }
+
+    // concentric circles have no preferred direction
+    let normal = offset.try_normalize().unwrap_or(Vec2::X);
+    let depth = radii - distance;
+    let point = a.center + normal * (a.radius - depth * 0.5);
+
+    Some(Contact { normal, depth, points: vec![point] })
+}
+
+pub fn circle_vs_aabb(circle: Circle, rect: Rect) -> Option<Contact> {
+    circle_vs_polygon(circle, &rect.corners())
+}
+
+/// Circle against a convex polygon of either winding, with the normal pointing from the circle to the polygon.
+pub fn circle_vs_polygon(circle: Circle, polygon: &[Vec2]) -> Option<Contact> {
+    if polygon.len() < 3 {
+        return None;
+    }
+
+    let closest = polygon::edges(polygon)
+        .map(|(a, b)| closest_point_on_segment(a, b, circle.center))
+        .min_by(|a, b| a.distance_squared(circle.center).total_cmp(&b.distance_squared(circle.center)))?;
+
+    let offset = closest - circle.center;
+    let distance = offset.length();
+
+    if point_in_polygon(polygon, circle.center) {
+        // the circle center is inside, so the polygon has to move past it to separate
+        let normal = (-offset).try_normalize().unwrap_or(Vec2::X);
+        return Some(Contact { normal, depth: circle.radius + distance, points: vec![closest] });
+    }
+
+    if distance > circle.radius {
+        return None;
+    }
+
+    let normal = offset.try_normalize().unwrap_or(Vec2::X);
+    Some(Contact { normal, depth: circle.radius - distance, points: vec![closest] })
+}
+
+/// Separating axis test between two convex polygons of either winding.
+///
+/// Contact points are found by clipping the edge of the second polygon that faces the first against the edge with
+/// the least overlap, giving up to two points.
+pub fn polygon_vs_polygon(a: &[Vec2], b: &[Vec2]) -> Option<Contact> {
+    if a.len() < 3 || b.len() < 3 {
+        return None;
+    }
+
+    let a = counter_clockwise(a);
+    let b = counter_clockwise(b);
+
+    let (edge_a, separation_a) = max_separation(&a, &b);
+    if separation_a > 0.0 {
+        return None;
+    }
+    let (edge_b, separation_b) = max_separation(&b, &a);
+    if separation_b > 0.0 {
+        return None;
+    }
+
+    // prefer the first polygon as the reference unless the second is clearly better, to avoid flip-flopping
+    let (reference, incident, edge, flipped) = if separation_b > separation_a + 0.001 {
+        (&b, &a, edge_b, true)
+    } else {
+        (&a, &b, edge_a, false)
+    };
+
+    let v1 = reference[edge];
+    let v2 = reference[(edge + 1) % reference.len()];
+    let tangent = (v2 - v1).normalize();
+    let normal = -tangent.perpendicular();
+
+    // the incident edge is the one most opposed to the reference normal
+    let incident_edge = (0..incident.len())
+        .min_by(|i, j| {
+            edge_normal(incident, *i).dot(normal).total_cmp(&edge_normal(incident, *j).dot(normal))
+        })
+        .unwrap_or(0);
+    let segment = [incident[incident_edge], incident[(incident_edge + 1) % incident.len()]];
+
+    let clipped = clip(&segment, tangent, tangent.dot(v1));
+    let clipped = clip(&clipped, -tangent, -tangent.dot(v2));
+
+    let mut depth: f32 = 0.0;
+    let points: Vec<Vec2> = clipped
+        .into_iter()
+        .filter(|point| {
+            let separation = normal.dot(*point - v1);
+            depth = depth.max(-separation);
+            separation <= 0.0
+        })
+        .collect();
+
+    let depth = if points.is_empty() { -separation_a.max(separation_b) } else { depth };
+    let normal = if flipped { -normal } else { normal };
+
+    Some(Contact { normal, depth, points })
+}
+
+/// Point where two segments cross, `None` if they miss or are parallel.
+pub fn segment_intersection(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> Option<Vec2> {
+    let (a, b) = (a2 - a1, b2 - b1);
+    let denominator = a.cross(b);
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    let offset = b1 - a1;
+    let t = offset.cross(b) / denominator;
+    let u = offset.cross(a) / denominator;
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some(a1 + a * t)
+    } else {
+        None
+    }
+}
+
+pub fn closest_point_on_segment(a: Vec2, b: Vec2, point: Vec2) -> Vec2 {
+    let edge = b - a;
+    let length_squared = edge.length_squared();
+    if length_squared <= f32::EPSILON {
+        return a;
+    }
+
+    let t = ((point - a).dot(edge) / length_squared).clamp(0.0, 1.0);
+    a + edge * t
+}
+
+impl Ray {
+    /// The direction is normalised, so hit distances are in the same units as positions.
+    pub fn new(origin: Vec2, direction: Vec2) -> Ray {
+        Ray { origin, direction: direction.normalize() }
+    }
+
+    pub fn at(self, distance: f32) -> Vec2 {
+        self.origin + self.direction * distance
+    }
+
+    pub fn cast_segment(self, a: Vec2, b: Vec2, max_distance: f32) -> Option<RayHit> {
+        let edge = b - a;
+        let denominator = self.direction.cross(edge);
+        if denominator.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let offset = a - self.origin;
+        let distance = offset.cross(edge) / denominator;
+        let t = offset.cross(self.direction) / denominator;
+        if distance < 0.0 || distance > max_distance || !(0.0..=1.0).contains(&t) {
+            return None;
+        }
+
+        let mut normal = edge.perpendicular().normalize();
+        if normal.dot(self.direction) > 0.0 {
+            normal = -normal;
+        }
+
+        Some(RayHit { point: self.at(distance), normal, distance })
+    }
+
+    /// Rays starting inside the rect hit it straight away, with the normal facing back along the ray.
+    pub fn cast_aabb(self, rect: Rect, max_distance: f32) -> Option<RayHit> {
+        if rect.contains_point(self.origin) {
+            return Some(RayHit { point: self.origin, normal: -self.direction, distance: 0.0 });
+        }
+
+        let mut near = 0.0_f32;
+        let mut far = max_distance;
+        let mut normal = Vec2::ZERO;
+
+        for axis in 0..2 {
+            let (origin, direction) = (self.origin[axis], self.direction[axis]);
+            let (min, max) = (rect.min[axis], rect.max[axis]);
+
+            if direction.abs() <= f32::EPSILON {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let (mut t1, mut t2) = ((min - origin) / direction, (max - origin) / direction);
+            let mut sign = -1.0;
+            if t1 > t2 {
+                std::mem::swap(&mut t1, &mut t2);
+                sign = 1.0;
+            }
+
+            if t1 > near {
+                near = t1;
+                normal = Vec2::ZERO;
+                normal[axis] = sign;
+            }
+            far = far.min(t2);
+            if near > far {
+                return None;
+            }
+        }
+
+        Some(RayHit { point: self.at(near), normal, distance: near })
+    }
+
+    /// Rays starting inside the circle hit it straight away, with the normal facing back along the ray.
+    pub fn cast_circle(self, circle: Circle, max_distance: f32) -> Option<RayHit> {
+        if circle.contains_point(self.origin) {
+            return Some(RayHit { point: self.origin, normal: -self.direction, distance: 0.0 });
+        }
+
+        let offset = self.origin - circle.center;
+        let b = offset.dot(self.direction);
+        let c = offset.length_squared() - circle.radius * circle.radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let distance = -b - discriminant.sqrt();
+        if distance < 0.0 || distance > max_distance {
+            return None;
+        }
+
+        let point = self.at(distance);
+        Some(RayHit { point, normal: (point - circle.center).normalize(), distance })
+    }
+
+    /// Closest hit against any edge of the polygon, which does not need to be convex.
+    pub fn cast_polygon(self, polygon: &[Vec2], max_distance: f32) -> Option<RayHit> {
+        if polygon.len() >= 3 && point_in_polygon(polygon, self.origin) {
+            return Some(RayHit { point: self.origin, normal: -self.direction, distance: 0.0 });
+        }
+
+        polygon::edges(polygon)
+            .filter_map(|(a, b)| self.cast_segment(a, b, max_distance))
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+}
+
+fn counter_clockwise(points: &[Vec2]) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    polygon::set_winding(&mut points, Winding::CounterClockwise);
+    points
+}
+
+// outward normal of a counter-clockwise polygon's edge
+fn edge_normal(points: &[Vec2], edge: usize) -> Vec2 {
+    let (a, b) = (points[edge], points[(edge + 1) % points.len()]);
+    -(b - a).perpendicular().normalize()
+}
+
+// the edge of `a` that `b` is furthest outside of, negative when every edge overlaps
+fn max_separation(a: &[Vec2], b: &[Vec2]) -> (usize, f32) {
+    (0..a.len())
+        .map(|edge| {
+            let normal = edge_normal(a, edge);
+            let separation = b
+                .iter()
+                .map(|point| normal.dot(*point - a[edge]))
+                .fold(f32::MAX, f32::min);
+            (edge, separation)
+        })
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+        .unwrap_or((0, f32::MAX))
+}
+
+// keeps the part of the segment where `direction . point >= offset`
+fn clip(segment: &[Vec2], direction: Vec2, offset: f32) -> Vec<Vec2> {
+    if segment.len() < 2 {
+        return segment.to_vec();
+    }
+
+    let (p1, p2) = (segment[0], segment[1]);
+    let (d1, d2) = (direction.dot(p1) - offset, direction.dot(p2) - offset);
+
+    let mut clipped = Vec::with_capacity(2);
+    if d1 >= -EPSILON {
+        clipped.push(p1);
+    }
+    if d2 >= -EPSILON {
+        clipped.push(p2);
+    }
+    if d1 * d2 < 0.0 && clipped.len() < 2 {
+        clipped.push(p1 + (p2 - p1) * (d1 / (d1 - d2)));
+    }
+
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(min: Vec2, size: f32) -> Vec<Vec2> {
+        vec![min, min + Vec2::new(size, 0.0), min + Vec2::splat(size), min + Vec2::new(0.0, size)]
+    }
+
+    fn near(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 1e-5
+    }
+
+    #[test]
+    fn aabb_contacts() {
+        let a = Rect::new(Vec2::ZERO, Vec2::splat(2.0));
+
+        let contact = aabb_vs_aabb(a, Rect::new(Vec2::new(1.0, 0.5), Vec2::new(3.0, 2.5))).unwrap();
+        assert_eq!(contact.normal, Vec2::X);
+        assert_eq!(contact.depth, 1.0);
+
+        let contact = aabb_vs_aabb(a, Rect::new(Vec2::new(0.5, -1.5), Vec2::new(1.5, 0.5))).unwrap();
+        assert_eq!(contact.normal, -Vec2::Y);
+        assert_eq!(contact.depth, 0.5);
+
+        let contact = aabb_vs_aabb(a, Rect::new(Vec2::new(2.0, 0.0), Vec2::new(4.0, 2.0))).unwrap();
+        assert_eq!(contact.normal, Vec2::X);
+        assert_eq!(contact.depth, 0.0);
+
+        assert!(aabb_vs_aabb(a, Rect::new(Vec2::new(2.5, 0.0), Vec2::new(4.0, 2.0))).is_none());
+    }
+
+    #[test]
+    fn circle_contacts() {
+        let a = Circle::new(Vec2::ZERO, 1.0);
+
+        let contact = circle_vs_circle(a, Circle::new(Vec2::new(1.5, 0.0), 1.0)).unwrap();
+        assert_eq!(contact.normal, Vec2::X);
+        assert!((contact.depth - 0.5).abs() < 1e-6);
+        assert!(near(contact.points[0], Vec2::new(0.75, 0.0)));
+
+        let contact = circle_vs_circle(a, Circle::new(Vec2::new(0.0, -2.0), 1.0)).unwrap();
+        assert_eq!(contact.normal, -Vec2::Y);
+        assert_eq!(contact.depth, 0.0);
+
+        assert!(circle_vs_circle(a, Circle::new(Vec2::new(2.1, 0.0), 1.0)).is_none());
+    }
+
+    #[test]
+    fn polygon_contacts() {
+        let a = square(Vec2::ZERO, 2.0);
+
+        // either winding of the second polygon gives the same contact
+        let mut b = square(Vec2::new(1.5, 0.5), 2.0);
+        for _ in 0..2 {
+            let contact = polygon_vs_polygon(&a, &b).unwrap();
+            assert!(near(contact.normal, Vec2::X));
+            assert!((contact.depth - 0.5).abs() < 1e-5);
+            assert_eq!(contact.points.len(), 2);
+            assert!(contact.points.iter().all(|point| (point.x - 1.5).abs() < 1e-5));
+            b.reverse();
+        }
+
+        let contact = polygon_vs_polygon(&a, &square(Vec2::new(0.5, 2.0), 1.0)).unwrap();
+        assert!(near(contact.normal, Vec2::Y));
+        assert!(contact.depth.abs() < 1e-5);
+
+        assert!(polygon_vs_polygon(&a, &square(Vec2::new(3.0, 0.0), 2.0)).is_none());
+        // bounding boxes overlap, but the triangle's long edge separates them
+        let triangle = [Vec2::new(3.5, 1.0), Vec2::new(4.0, 4.0), Vec2::new(1.0, 3.5)];
+        assert!(polygon_vs_polygon(&a, &triangle).is_none());
+    }
+
+    #[test]
+    fn points_in_polygons() {
+        let l_shape = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(3.0, 0.0),
+            Vec2::new(3.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 3.0),
+            Vec2::new(0.0, 3.0),
+        ];
+        assert!(point_in_polygon(&l_shape, Vec2::new(0.5, 2.5)));
+        assert!(point_in_polygon(&l_shape, Vec2::new(2.5, 0.5)));
+        assert!(!point_in_polygon(&l_shape, Vec2::new(2.0, 2.0)));
+        assert!(!point_in_polygon(&l_shape, Vec2::new(-1.0, 0.5)));
+    }
+
+    #[test]
+    fn segment_intersections() {
+        let crossing = segment_intersection(Vec2::ZERO, Vec2::splat(2.0), Vec2::new(0.0, 2.0), Vec2::new(2.0, 0.0));
+        assert!(near(crossing.unwrap(), Vec2::splat(1.0)));
+
+        let parallel = segment_intersection(Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::new(1.0, 1.0));
+        assert!(parallel.is_none());
+
+        // overlapping collinear segments share a whole range rather than a point
+        let collinear = segment_intersection(Vec2::ZERO, Vec2::new(2.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(3.0, 0.0));
+        assert!(collinear.is_none());
+
+        let short = segment_intersection(Vec2::ZERO, Vec2::X, Vec2::new(2.0, -1.0), Vec2::new(2.0, 1.0));
+        assert!(short.is_none());
+    }
+
+    #[test]
+    fn ray_casts() {
+        let rect = Rect::new(Vec2::ZERO, Vec2::splat(2.0));
+        let ray = Ray::new(Vec2::new(-5.0, 1.0), Vec2::new(3.0, 0.0));
+
+        let hit = ray.cast_aabb(rect, 100.0).unwrap();
+        assert!(near(hit.point, Vec2::new(0.0, 1.0)));
+        assert_eq!(hit.normal, -Vec2::X);
+        assert!((hit.distance - 5.0).abs() < 1e-5);
+        assert!(ray.cast_aabb(rect, 4.0).is_none());
+
+        let hit = ray.cast_polygon(&square(Vec2::ZERO, 2.0), 100.0).unwrap();
+        assert!(near(hit.point, Vec2::new(0.0, 1.0)));
+        assert!(near(hit.normal, -Vec2::X));
+        assert!((hit.distance - 5.0).abs() < 1e-5);
+
+        let hit = ray.cast_circle(Circle::new(Vec2::new(3.0, 1.0), 1.0), 100.0).unwrap();
+        assert!(near(hit.point, Vec2::new(2.0, 1.0)));
+        assert!(near(hit.normal, -Vec2::X));
+        assert!((hit.distance - 7.0).abs() < 1e-5);
+
+        let miss = Ray::new(Vec2::new(-5.0, 1.0), Vec2::Y);
+        assert!(miss.cast_aabb(rect, 100.0).is_none());
+        assert!(miss.cast_circle(Circle::new(Vec2::new(3.0, 1.0), 1.0), 100.0).is_none());
+        assert!(miss.cast_polygon(&square(Vec2::ZERO, 2.0), 100.0).is_none());
+
+        let inside = Ray::new(Vec2::splat(1.0), Vec2::X);
+        for hit in [
+            inside.cast_aabb(rect, 100.0),
+            inside.cast_circle(Circle::new(Vec2::splat(1.0), 1.0), 100.0),
+            inside.cast_polygon(&square(Vec2::ZERO, 2.0), 100.0),
+        ] {
+            let hit = hit.unwrap();
+            assert_eq!(hit.point, Vec2::splat(1.0));
+            assert_eq!(hit.normal, -Vec2::X);
+            assert_eq!(hit.distance, 0.0);
+        }
+    }
+}
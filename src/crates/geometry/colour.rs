@@ -0,0 +1,333 @@
+use bytemuck::{Pod, Zeroable};
+
+use std::ops::{Add, Div, Mul, Sub};
+
+use vulkano::pipeline::graphics::vertex_input::{VertexMember, VertexMemberTy};
+
+use super::vector::{Vec3, Vec4};
+
+/// Straight alpha RGBA colour with every channel in the 0-1 range.
+///
+/// Channels are stored as given, so whether they are sRGB or linear is up to the caller. Colours built from hex
+/// strings, bytes and HSV or HSL are sRGB.
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct Colour {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Colour {
+    pub const TRANSPARENT: Colour = Colour::rgba(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Colour = Colour::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Colour = Colour::rgb(1.0, 1.0, 1.0);
+    pub const GREY: Colour = Colour::rgb(0.5, 0.5, 0.5);
+    pub const RED: Colour = Colour::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Colour = Colour::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Colour = Colour::rgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Colour = Colour::rgb(1.0, 1.0, 0.0);
+    pub const CYAN: Colour = Colour::rgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Colour = Colour::rgb(1.0, 0.0, 1.0);
+    pub const ORANGE: Colour = Colour::rgb(1.0, 0.647, 0.0);
+    pub const PURPLE: Colour = Colour::rgb(0.5, 0.0, 0.5);
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Colour {
+        Colour { r, g, b, a: 1.0 }
+    }
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Colour {
+        Colour { r, g, b, a }
+    }
+
+    pub fn from_rgb8(r: u8, g: u8, b: u8) -> Colour {
+        Colour::from_rgba8(r, g, b, 255)
+    }
+
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Colour {
+        Colour::rgba(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0)
+    }
+
+    /// Channels clamped and rounded to bytes.
+    pub fn to_rgba8(self) -> [u8; 4] {
+        self.to_array().map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    /// Parses `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa`, with or without the `#`.
+    pub fn from_hex(hex: &str) -> Option<Colour> {
+        let hex = hex.trim();
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        let digits: Vec<u8> = hex.chars().map(|digit| digit.to_digit(16).map(|digit| digit as u8)).collect::<Option<_>>()?;
+
+        match digits[..] {
+            [r, g, b] => Some(Colour::from_rgb8(r * 17, g * 17, b * 17)),
+            [r, g, b, a] => Some(Colour::from_rgba8(r * 17, g * 17, b * 17, a * 17)),
+            [r1, r2, g1, g2, b1, b2] => Some(Colour::from_rgb8(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)),
+            [r1, r2, g1, g2, b1, b2, a1, a2] => {
+                Some(Colour::from_rgba8(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, a1 * 16 + a2))
+            }
+            _ => None,
+        }
+    }
+
+    /// `#rrggbb`, or `#rrggbbaa` when the colour is not opaque.
+    pub fn to_hex(self) -> String {
+        let [r, g, b, a] = self.to_rgba8();
+        if a == 255 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+
+    /// The basic CSS colour keywords, ignoring case.
+    pub fn from_name(name: &str) -> Option<Colour> {
+        let [r, g, b] = match name.trim().to_ascii_lowercase().as_str() {
+            "transparent" => return Some(Colour::TRANSPARENT),
+            "black" => [0, 0, 0],
+            "white" => [255, 255, 255],
+            "red" => [255, 0, 0],
+            "lime" => [0, 255, 0],
+            "green" => [0, 128, 0],
+            "blue" => [0, 0, 255],
+            "yellow" => [255, 255, 0],
+            "cyan" | "aqua" => [0, 255, 255],
+            "magenta" | "fuchsia" => [255, 0, 255],
+            "gray" | "grey" => [128, 128, 128],
+            "silver" => [192, 192, 192],
+            "maroon" => [128, 0, 0],
+            "olive" => [128, 128, 0],
+            "navy" => [0, 0, 128],
+            "purple" => [128, 0, 128],
+            "teal" => [0, 128, 128],
+            "orange" => [255, 165, 0],
+            _ => return None,
+        };
+
+        Some(Colour::from_rgb8(r, g, b))
+    }
+
+    /// Hue in degrees, saturation and value in 0-1.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32, alpha: f32) -> Colour {
+        let chroma = value * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let offset = value - chroma;
+        Colour::rgba(r + offset, g + offset, b + offset, alpha)
+    }
+
+    /// Hue in degrees, saturation and lightness in 0-1.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Colour {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let offset = lightness - chroma * 0.5;
+        Colour::rgba(r + offset, g + offset, b + offset, alpha)
+    }
+
+    /// Hue in degrees, saturation and value, with a hue of zero for greys.
+    pub fn to_hsv(self) -> Vec3 {
+        let (max, min) = (self.r.max(self.g).max(self.b), self.r.min(self.g).min(self.b));
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        Vec3::new(self.hue(max, min), saturation, max)
+    }
+
+    /// Hue in degrees, saturation and lightness, with a hue of zero for greys.
+    pub fn to_hsl(self) -> Vec3 {
+        let (max, min) = (self.r.max(self.g).max(self.b), self.r.min(self.g).min(self.b));
+        let lightness = (max + min) * 0.5;
+        let saturation = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()) };
+        Vec3::new(self.hue(max, min), saturation, lightness)
+    }
+
+    fn hue(self, max: f32, min: f32) -> f32 {
+        let chroma = max - min;
+        if chroma <= 0.0 {
+            return 0.0;
+        }
+
+        let hue = if max == self.r {
+            ((self.g - self.b) / chroma).rem_euclid(6.0)
+        } else if max == self.g {
+            (self.b - self.r) / chroma + 2.0
+        } else {
+            (self.r - self.g) / chroma + 4.0
+        };
+        hue * 60.0
+    }
+
+    /// Decodes sRGB channels to linear light, leaving alpha alone.
+    pub fn to_linear(self) -> Colour {
+        let decode = |channel: f32| {
+            if channel <= 0.04045 {
+                channel / 12.92
+            } else {
+                ((channel + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        Colour::rgba(decode(self.r), decode(self.g), decode(self.b), self.a)
+    }
+
+    /// Encodes linear channels as sRGB, leaving alpha alone.
+    pub fn to_srgb(self) -> Colour {
+        let encode = |channel: f32| {
+            if channel <= 0.0031308 {
+                channel * 12.92
+            } else {
+                1.055 * channel.powf(1.0 / 2.4) - 0.055
+            }
+        };
+        Colour::rgba(encode(self.r), encode(self.g), encode(self.b), self.a)
+    }
+
+    pub fn premultiply(self) -> Colour {
+        Colour::rgba(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    /// Undoes `premultiply`, giving transparent black for a fully transparent colour.
+    pub fn unpremultiply(self) -> Colour {
+        if self.a <= 0.0 {
+            return Colour::TRANSPARENT;
+        }
+        Colour::rgba(self.r / self.a, self.g / self.a, self.b / self.a, self.a)
+    }
+
+    pub fn with_alpha(self, alpha: f32) -> Colour {
+        Colour { a: alpha, ..self }
+    }
+
+    pub fn lerp(self, other: Colour, t: f32) -> Colour {
+        self + (other - self) * t
+    }
+
+    pub fn clamp(self) -> Colour {
+        Colour::from(self.to_array().map(|channel| channel.clamp(0.0, 1.0)))
+    }
+
+    /// Porter-Duff source over, drawing `self` on top of `below`.
+    pub fn over(self, below: Colour) -> Colour {
+        let a = self.a + below.a * (1.0 - self.a);
+        if a <= 0.0 {
+            return Colour::TRANSPARENT;
+        }
+
+        let blend = |top: f32, bottom: f32| (top * self.a + bottom * below.a * (1.0 - self.a)) / a;
+        Colour::rgba(blend(self.r, below.r), blend(self.g, below.g), blend(self.b, below.b), a)
+    }
+
+    /// Multiplies the colours together, darkening `below`, keeping the alpha of `below`.
+    pub fn multiply(self, below: Colour) -> Colour {
+        self.blend(below, |top, bottom| top * bottom)
+    }
+
+    /// The inverse of multiply, lightening `below`, keeping the alpha of `below`.
+    pub fn screen(self, below: Colour) -> Colour {
+        self.blend(below, |top, bottom| top + bottom - top * bottom)
+    }
+
+    /// Adds the colours together, clamped to 1, keeping the alpha of `below`.
+    pub fn additive(self, below: Colour) -> Colour {
+        self.blend(below, |top, bottom| (top + bottom).min(1.0))
+    }
+
+    // applies a separable blend mode, faded in by the alpha of `self`
+    fn blend(self, below: Colour, mode: impl Fn(f32, f32) -> f32) -> Colour {
+        let mix = |top: f32, bottom: f32| bottom + (mode(top, bottom) - bottom) * self.a;
+        Colour::rgba(mix(self.r, below.r), mix(self.g, below.g), mix(self.b, below.b), below.a)
+    }
+
+    pub fn to_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+}
+
+// chroma split over red, green and blue for a hue in degrees, before the lightness offset is added
+fn hue_to_rgb(hue: f32, chroma: f32) -> (f32, f32, f32) {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    }
+}
+
+impl From<[f32; 4]> for Colour {
+    fn from([r, g, b, a]: [f32; 4]) -> Colour {
+        Colour::rgba(r, g, b, a)
+    }
+}
+
+impl From<[f32; 3]> for Colour {
+    fn from([r, g, b]: [f32; 3]) -> Colour {
+        Colour::rgb(r, g, b)
+    }
+}
+
+impl From<Vec3> for Colour {
+    fn from(vector: Vec3) -> Colour {
+        Colour::rgb(vector.x, vector.y, vector.z)
+    }
+}
+
+impl From<Vec4> for Colour {
+    fn from(vector: Vec4) -> Colour {
+        Colour::rgba(vector.x, vector.y, vector.z, vector.w)
+    }
+}
+
+impl From<Colour> for Vec4 {
+    fn from(colour: Colour) -> Vec4 {
+        Vec4::new(colour.r, colour.g, colour.b, colour.a)
+    }
+}
+
+impl Add for Colour {
+    type Output = Colour;
+
+    fn add(self, colour: Colour) -> Colour {
+        Colour::rgba(self.r + colour.r, self.g + colour.g, self.b + colour.b, self.a + colour.a)
+    }
+}
+
+impl Sub for Colour {
+    type Output = Colour;
+
+    fn sub(self, colour: Colour) -> Colour {
+        Colour::rgba(self.r - colour.r, self.g - colour.g, self.b - colour.b, self.a - colour.a)
+    }
+}
+
+impl Mul for Colour {
+    type Output = Colour;
+
+    fn mul(self, colour: Colour) -> Colour {
+        Colour::rgba(self.r * colour.r, self.g * colour.g, self.b * colour.b, self.a * colour.a)
+    }
+}
+
+impl Div for Colour {
+    type Output = Colour;
+
+    fn div(self, colour: Colour) -> Colour {
+        Colour::rgba(self.r / colour.r, self.g / colour.g, self.b / colour.b, self.a / colour.a)
+    }
+}
+
+impl Mul<f32> for Colour {
+    type Output = Colour;
+
+    fn mul(self, scale: f32) -> Colour {
+        Colour::rgba(self.r * scale, self.g * scale, self.b * scale, self.a * scale)
+    }
+}
+
+// lets colours be used directly as fields passed to `impl_vertex!`
+unsafe impl VertexMember for Colour {
+    fn format() -> (VertexMemberTy, usize) {
+        (VertexMemberTy::F32, 4)
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+use super::colour::Colour;
+use super::vector::{Vec2, Vec3};
+use super::Vertex;
+
+/// Colour space that ramps blend between stops in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Blends the stored channels directly, which is what CSS and SVG do.
+    #[default]
+    Srgb,
+    /// Blends in linear light, avoiding the dark band between saturated colours.
+    Linear,
+    /// Blends hue the short way round the colour wheel.
+    Hsv,
+}
+
+/// What happens to a gradient outside of its 0-1 range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Spread {
+    /// Keeps the colour of the nearest end.
+    #[default]
+    Pad,
+    Repeat,
+    /// Repeats, running backwards every other time.
+    Reflect,
+}
+
+/// Colours at offsets along 0-1, blended between neighbouring stops.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColourRamp {
+    stops: Vec<(f32, Colour)>,
+    pub interpolation: Interpolation,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GradientShape {
+    /// Runs from `start` to `end`, constant along lines perpendicular to them.
+    Linear { start: Vec2, end: Vec2 },
+    /// Runs from `center` out to `radius`.
+    Radial { center: Vec2, radius: f32 },
+    /// Sweeps counter-clockwise around `center`, starting at `start_angle`.
+    Conic { center: Vec2, start_angle: f32 },
+}
+
+/// A gradient shape filled with a colour ramp, applied to meshes by recolouring their vertices.
+///
+/// Colours are only evaluated at vertices and blended across triangles, so coarse meshes should be subdivided with
+/// `apply_subdivided` first. Conic gradients will still blend across their seam.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub ramp: ColourRamp,
+    pub spread: Spread,
+}
+
+impl ColourRamp {
+    pub fn new() -> ColourRamp {
+        ColourRamp::default()
+    }
+
+    /// Ramp from `start` at 0 to `end` at 1.
+    pub fn between(start: Colour, end: Colour) -> ColourRamp {
+        ColourRamp::new().with_stop(0.0, start).with_stop(1.0, end)
+    }
+
+    /// Evenly spaced stops.
+    pub fn from_colours(colours: &[Colour]) -> ColourRamp {
+        let last = colours.len().saturating_sub(1).max(1) as f32;
+        colours.iter().enumerate().fold(ColourRamp::new(), |ramp, (i, colour)| ramp.with_stop(i as f32 / last, *colour))
+    }
+
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> ColourRamp {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn with_stop(mut self, offset: f32, colour: Colour) -> ColourRamp {
+        self.add_stop(offset, colour);
+        self
+    }
+
+    /// Stops at the same offset are kept in the order they were added, giving a hard edge.
+    pub fn add_stop(&mut self, offset: f32, colour: Colour) {
+        let offset = offset.clamp(0.0, 1.0);
+        let index = self.stops.partition_point(|(other, _)| *other <= offset);
+        self.stops.insert(index, (offset, colour));
+    }
+
+    pub fn stops(&self) -> &[(f32, Colour)] {
+        &self.stops
+    }
+
+    /// Colour at `t`, clamped to the first and last stops. An empty ramp is transparent.
+    pub fn sample(&self, t: f32) -> Colour {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Colour::TRANSPARENT,
+        };
+        if t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+
+        let next = self.stops.partition_point(|(offset, _)| *offset <= t);
+        let (start, end) = (self.stops[next - 1], self.stops[next]);
+        let amount = (t - start.0) / (end.0 - start.0);
+        self.blend(start.1, end.1, amount)
+    }
+
+    fn blend(&self, start: Colour, end: Colour, t: f32) -> Colour {
+        match self.interpolation {
+            Interpolation::Srgb => start.lerp(end, t),
+            Interpolation::Linear => start.to_linear().lerp(end.to_linear(), t).to_srgb(),
+            Interpolation::Hsv => {
+                let (from, to) = (start.to_hsv(), end.to_hsv());
+                let turn = (to.x - from.x + 180.0).rem_euclid(360.0) - 180.0;
+                let hsv = Vec3::new(from.x + turn * t, from.y + (to.y - from.y) * t, from.z + (to.z - from.z) * t);
+                Colour::from_hsv(hsv.x, hsv.y, hsv.z, start.a + (end.a - start.a) * t)
+            }
+        }
+    }
+}
+
+impl Gradient {
+    pub fn linear(start: Vec2, end: Vec2, ramp: ColourRamp) -> Gradient {
+        Gradient { shape: GradientShape::Linear { start, end }, ramp, spread: Spread::Pad }
+    }
+
+    pub fn radial(center: Vec2, radius: f32, ramp: ColourRamp) -> Gradient {
+        Gradient { shape: GradientShape::Radial { center, radius }, ramp, spread: Spread::Pad }
+    }
+
+    pub fn conic(center: Vec2, start_angle: f32, ramp: ColourRamp) -> Gradient {
+        Gradient { shape: GradientShape::Conic { center, start_angle }, ramp, spread: Spread::Pad }
+    }
+
+    pub fn with_spread(mut self, spread: Spread) -> Gradient {
+        self.spread = spread;
+        self
+    }
+
+    /// Where `point` falls along the gradient, before the spread is applied.
+    pub fn parameter(&self, point: Vec2) -> f32 {
+        match self.shape {
+            GradientShape::Linear { start, end } => {
+                let direction = end - start;
+                let length_squared = direction.length_squared();
+                if length_squared <= f32::EPSILON {
+                    return 0.0;
+                }
+                (point - start).dot(direction) / length_squared
+            }
+            GradientShape::Radial { center, radius } => {
+                if radius <= 0.0 {
+                    return 1.0;
+                }
+                point.distance(center) / radius
+            }
+            GradientShape::Conic { center, start_angle } => ((point - center).angle() - start_angle).rem_euclid(TAU) / TAU,
+        }
+    }
+
+    pub fn colour_at(&self, point: Vec2) -> Colour {
+        let t = self.parameter(point);
+        let t = match self.spread {
+            Spread::Pad => t,
+            Spread::Repeat => t.rem_euclid(1.0),
+            Spread::Reflect => 1.0 - (t.rem_euclid(2.0) - 1.0).abs(),
+        };
+        self.ramp.sample(t)
+    }
+
+    /// Replaces the colour of every vertex with the gradient at its position.
+    pub fn apply(&self, vertices: &mut [Vertex]) {
+        for vertex in vertices {
+            vertex.colour = self.colour_at(vertex.position);
+        }
+    }
+
+    /// Subdivides the mesh until no edge is longer than `max_edge`, then applies the gradient.
+    pub fn apply_subdivided(&self, mesh: &mut (Vec<Vertex>, Vec<u32>), max_edge: f32) {
+        subdivide(mesh, max_edge);
+        self.apply(&mut mesh.0);
+    }
+}
+
+/// Splits triangle edges longer than `max_edge` in half until none are left, sharing the new vertices between
+/// neighbouring triangles so no cracks open up.
+///
+/// Gives up after sixteen rounds, which is enough to cut every edge down by a factor of 65536.
+pub fn subdivide(mesh: &mut (Vec<Vertex>, Vec<u32>), max_edge: f32) {
+    if max_edge <= 0.0 {
+        return;
+    }
+    let limit = max_edge * max_edge;
+
+    for _ in 0..16 {
+        let (vertices, indices) = mesh;
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut split = |a: u32, b: u32, vertices: &mut Vec<Vertex>| -> Option<u32> {
+            let (start, end) = (vertices[a as usize], vertices[b as usize]);
+            if start.position.distance_squared(end.position) <= limit {
+                return None;
+            }
+
+            let key = (a.min(b), a.max(b));
+            Some(*midpoints.entry(key).or_insert_with(|| {
+                vertices.push(start.lerp(end, 0.5));
+                vertices.len() as u32 - 1
+            }))
+        };
+
+        let mut subdivided = Vec::with_capacity(indices.len());
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+            let middles = [split(a, b, vertices), split(b, c, vertices), split(c, a, vertices)];
+
+            match middles {
+                [None, None, None] => subdivided.extend_from_slice(&[a, b, c]),
+                [Some(ab), Some(bc), Some(ca)] => {
+                    subdivided.extend_from_slice(&[a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+                }
+                _ => {
+                    // fan out from a split point, which is never in line with the two points it connects to
+                    let mut outline = Vec::with_capacity(5);
+                    for (corner, middle) in [a, b, c].into_iter().zip(middles) {
+                        outline.push(corner);
+                        outline.extend(middle);
+                    }
+                    let start = outline.iter().position(|index| middles.contains(&Some(*index))).unwrap_or(0);
+                    outline.rotate_left(start);
+                    for i in 1..outline.len() - 1 {
+                        subdivided.extend_from_slice(&[outline[0], outline[i], outline[i + 1]]);
+                    }
+                }
+            }
+        }
+
+        let done = subdivided.len() == indices.len();
+        *indices = subdivided;
+        if done {
+            break;
+        }
+    }
+}
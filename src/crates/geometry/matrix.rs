@@ -0,0 +1,466 @@
+use bytemuck::{Pod, Zeroable};
+
+use std::ops::{Mul, MulAssign};
+
+use super::vector::{Vec2, Vec3, Vec4, EPSILON};
+
+// both matrix types are column-major so they can be handed to GLSL as-is
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct Mat3 {
+    pub cols: [Vec3; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct Mat4 {
+    pub cols: [Vec4; 4],
+}
+
+impl Default for Mat3 {
+    fn default() -> Mat3 {
+        Mat3::IDENTITY
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Mat4 {
+        Mat4::IDENTITY
+    }
+}
+
+impl Mat3 {
+    pub const IDENTITY: Mat3 = Mat3 { cols: [Vec3::X, Vec3::Y, Vec3::Z] };
+
+    pub const fn from_cols(x: Vec3, y: Vec3, z: Vec3) -> Mat3 {
+        Mat3 { cols: [x, y, z] }
+    }
+
+    pub fn from_translation(translation: Vec2) -> Mat3 {
+        Mat3::from_cols(Vec3::X, Vec3::Y, translation.extend(1.0))
+    }
+
+    /// Counter-clockwise rotation in radians.
+    pub fn from_rotation(angle: f32) -> Mat3 {
+        let (sin, cos) = angle.sin_cos();
+        Mat3::from_cols(Vec3::new(cos, sin, 0.0), Vec3::new(-sin, cos, 0.0), Vec3::Z)
+    }
+
+    pub fn from_scale(scale: Vec2) -> Mat3 {
+        Mat3::from_cols(Vec3::new(scale.x, 0.0, 0.0), Vec3::new(0.0, scale.y, 0.0), Vec3::Z)
+    }
+
+    /// Equivalent to `translation * rotation * scale`.
+    pub fn from_scale_rotation_translation(scale: Vec2, angle: f32, translation: Vec2) -> Mat3 {
+        let (sin, cos) = angle.sin_cos();
+        Mat3::from_cols(
+            Vec3::new(cos * scale.x, sin * scale.x, 0.0),
+            Vec3::new(-sin * scale.y, cos * scale.y, 0.0),
+            translation.extend(1.0),
+        )
+    }
+
+    pub fn row(self, index: usize) -> Vec3 {
+        Vec3::new(self.cols[0][index], self.cols[1][index], self.cols[2][index])
+    }
+
+    pub fn transpose(self) -> Mat3 {
+        Mat3::from_cols(self.row(0), self.row(1), self.row(2))
+    }
+
+    pub fn determinant(self) -> f32 {
+        let [x, y, z] = self.cols;
+        x.dot(y.cross(z))
+    }
+
+    /// Returns `None` if the matrix is singular, however small or large its entries are otherwise.
+    pub fn inverse(self) -> Option<Mat3> {
+        let [x, y, z] = self.cols;
+        let determinant = self.determinant();
+        if is_singular(determinant, &[x.length(), y.length(), z.length()]) {
+            return None;
+        }
+
+        let adjugate = Mat3::from_cols(y.cross(z), z.cross(x), x.cross(y)).transpose();
+        let inverse = adjugate * (1.0 / determinant);
+        inverse.to_cols_array().iter().flatten().all(|value| value.is_finite()).then_some(inverse)
+    }
+
+    /// Transforms a point, applying translation.
+    pub fn transform_point(self, point: Vec2) -> Vec2 {
+        (self * point.extend(1.0)).truncate()
+    }
+
+    /// Transforms a direction, ignoring translation.
+    pub fn transform_vector(self, vector: Vec2) -> Vec2 {
+        (self * vector.extend(0.0)).truncate()
+    }
+
+    pub fn approx_eq(self, other: Mat3, epsilon: f32) -> bool {
+        self.cols.iter().zip(other.cols.iter()).all(|(a, b)| a.approx_eq(*b, epsilon))
+    }
+
+    pub fn to_cols_array(self) -> [[f32; 3]; 3] {
+        [self.cols[0].to_array(), self.cols[1].to_array(), self.cols[2].to_array()]
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Mat3;
+
+    fn mul(self, other: Mat3) -> Mat3 {
+        Mat3::from_cols(self * other.cols[0], self * other.cols[1], self * other.cols[2])
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, vector: Vec3) -> Vec3 {
+        self.cols[0] * vector.x + self.cols[1] * vector.y + self.cols[2] * vector.z
+    }
+}
+
+impl Mul<f32> for Mat3 {
+    type Output = Mat3;
+
+    fn mul(self, scalar: f32) -> Mat3 {
+        Mat3::from_cols(self.cols[0] * scalar, self.cols[1] * scalar, self.cols[2] * scalar)
+    }
+}
+
+impl MulAssign for Mat3 {
+    fn mul_assign(&mut self, other: Mat3) {
+        *self = *self * other;
+    }
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        cols: [
+            Vec4::new(1.0, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 1.0, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 1.0, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        ],
+    };
+
+    pub const fn from_cols(x: Vec4, y: Vec4, z: Vec4, w: Vec4) -> Mat4 {
+        Mat4 { cols: [x, y, z, w] }
+    }
+
+    pub fn from_translation(translation: Vec3) -> Mat4 {
+        let mut matrix = Mat4::IDENTITY;
+        matrix.cols[3] = translation.extend(1.0);
+        matrix
+    }
+
+    pub fn from_scale(scale: Vec3) -> Mat4 {
+        Mat4::from_cols(
+            Vec4::new(scale.x, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, scale.y, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, scale.z, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    pub fn from_rotation_x(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        Mat4::from_cols(
+            Vec4::new(1.0, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, cos, sin, 0.0),
+            Vec4::new(0.0, -sin, cos, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    pub fn from_rotation_y(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        Mat4::from_cols(
+            Vec4::new(cos, 0.0, -sin, 0.0),
+            Vec4::new(0.0, 1.0, 0.0, 0.0),
+            Vec4::new(sin, 0.0, cos, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    pub fn from_rotation_z(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        Mat4::from_cols(
+            Vec4::new(cos, sin, 0.0, 0.0),
+            Vec4::new(-sin, cos, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 1.0, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    /// Rotation of `angle` radians around a unit length `axis`.
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        let Vec3 { x, y, z } = axis;
+        let t = 1.0 - cos;
+
+        Mat4::from_cols(
+            Vec4::new(t * x * x + cos, t * x * y + sin * z, t * x * z - sin * y, 0.0),
+            Vec4::new(t * x * y - sin * z, t * y * y + cos, t * y * z + sin * x, 0.0),
+            Vec4::new(t * x * z + sin * y, t * y * z - sin * x, t * z * z + cos, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    /// Embeds a 2D affine transform into the xy plane.
+    pub fn from_affine_2d(matrix: Mat3) -> Mat4 {
+        let [x, y, translation] = matrix.cols;
+        Mat4::from_cols(
+            Vec4::new(x.x, x.y, 0.0, 0.0),
+            Vec4::new(y.x, y.y, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 1.0, 0.0),
+            Vec4::new(translation.x, translation.y, 0.0, 1.0),
+        )
+    }
+
+    /// Right-handed orthographic projection into Vulkan clip space.
+    ///
+    /// Vulkan's y axis points down, so `top` maps to -1 and `bottom` to 1, and depth is mapped to 0..1 with the
+    /// camera looking down -z.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+        let width = 1.0 / (right - left);
+        let height = 1.0 / (bottom - top);
+        let depth = 1.0 / (near - far);
+
+        Mat4::from_cols(
+            Vec4::new(2.0 * width, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 2.0 * height, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, depth, 0.0),
+            Vec4::new(-(right + left) * width, -(top + bottom) * height, near * depth, 1.0),
+        )
+    }
+
+    /// Right-handed perspective projection into Vulkan clip space, with the same conventions as `orthographic`.
+    pub fn perspective(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Mat4 {
+        let focal_length = 1.0 / (fov_y * 0.5).tan();
+        let depth = 1.0 / (near - far);
+
+        Mat4::from_cols(
+            Vec4::new(focal_length / aspect_ratio, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, -focal_length, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, far * depth, -1.0),
+            Vec4::new(0.0, 0.0, near * far * depth, 0.0),
+        )
+    }
+
+    pub fn row(self, index: usize) -> Vec4 {
+        Vec4::new(self.cols[0][index], self.cols[1][index], self.cols[2][index], self.cols[3][index])
+    }
+
+    pub fn transpose(self) -> Mat4 {
+        Mat4::from_cols(self.row(0), self.row(1), self.row(2), self.row(3))
+    }
+
+    pub fn determinant(self) -> f32 {
+        let m = self.to_cols_array_flat();
+        let adjugate = adjugate(&m);
+        m[0] * adjugate[0] + m[1] * adjugate[4] + m[2] * adjugate[8] + m[3] * adjugate[12]
+    }
+
+    /// Returns `None` if the matrix is singular, however small or large its entries are otherwise.
+    pub fn inverse(self) -> Option<Mat4> {
+        let m = self.to_cols_array_flat();
+        let adjugate = adjugate(&m);
+
+        let determinant = m[0] * adjugate[0] + m[1] * adjugate[4] + m[2] * adjugate[8] + m[3] * adjugate[12];
+        if is_singular(determinant, &self.cols.map(Vec4::length)) {
+            return None;
+        }
+
+        let inverse = adjugate.map(|value| value / determinant);
+        inverse.iter().all(|value| value.is_finite()).then_some(Mat4::from_cols_array_flat(&inverse))
+    }
+
+    /// Transforms a point, applying translation and the perspective divide.
+    pub fn transform_point(self, point: Vec3) -> Vec3 {
+        let transformed = self * point.extend(1.0);
+        if (transformed.w - 1.0).abs() <= EPSILON || transformed.w.abs() <= f32::EPSILON {
+            transformed.truncate()
+        } else {
+            transformed.truncate() / transformed.w
+        }
+    }
+
+    /// Transforms a direction, ignoring translation.
+    pub fn transform_vector(self, vector: Vec3) -> Vec3 {
+        (self * vector.extend(0.0)).truncate()
+    }
+
+    pub fn approx_eq(self, other: Mat4, epsilon: f32) -> bool {
+        self.cols.iter().zip(other.cols.iter()).all(|(a, b)| a.approx_eq(*b, epsilon))
+    }
+
+    pub fn to_cols_array(self) -> [[f32; 4]; 4] {
+        [self.cols[0].to_array(), self.cols[1].to_array(), self.cols[2].to_array(), self.cols[3].to_array()]
+    }
+
+    fn to_cols_array_flat(self) -> [f32; 16] {
+        bytemuck::cast(self)
+    }
+
+    fn from_cols_array_flat(array: &[f32; 16]) -> Mat4 {
+        bytemuck::cast(*array)
+    }
+}
+
+// the determinant is at most the product of the column lengths, so comparing against that keeps the test independent
+// of scale: a pixel-space projection has a determinant around 1e-9 and is perfectly invertible
+fn is_singular(determinant: f32, column_lengths: &[f32]) -> bool {
+    let largest: f32 = column_lengths.iter().product();
+    !determinant.is_finite() || determinant == 0.0 || determinant.abs() <= largest * EPSILON
+}
+
+// cofactor expansion of a column-major 4x4 matrix, transposed so that `m * adjugate(m) == det(m) * I`
+fn adjugate(m: &[f32; 16]) -> [f32; 16] {
+    let mut inv = [0.0; 16];
+
+    inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15]
+        + m[9] * m[7] * m[14] + m[13] * m[6] * m[11] - m[13] * m[7] * m[10];
+    inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15]
+        - m[8] * m[7] * m[14] - m[12] * m[6] * m[11] + m[12] * m[7] * m[10];
+    inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15]
+        + m[8] * m[7] * m[13] + m[12] * m[5] * m[11] - m[12] * m[7] * m[9];
+    inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14]
+        - m[8] * m[6] * m[13] - m[12] * m[5] * m[10] + m[12] * m[6] * m[9];
+    inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15]
+        - m[9] * m[3] * m[14] - m[13] * m[2] * m[11] + m[13] * m[3] * m[10];
+    inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15]
+        + m[8] * m[3] * m[14] + m[12] * m[2] * m[11] - m[12] * m[3] * m[10];
+    inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15]
+        - m[8] * m[3] * m[13] - m[12] * m[1] * m[11] + m[12] * m[3] * m[9];
+    inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14]
+        + m[8] * m[2] * m[13] + m[12] * m[1] * m[10] - m[12] * m[2] * m[9];
+    inv[2] = m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15]
+        + m[5] * m[3] * m[14] + m[13] * m[2] * m[7] - m[13] * m[3] * m[6];
+    inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15]
+        - m[4] * m[3] * m[14] - m[12] * m[2] * m[7] + m[12] * m[3] * m[6];
+    inv[10] = m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15]
+        + m[4] * m[3] * m[13] + m[12] * m[1] * m[7] - m[12] * m[3] * m[5];
+    inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14]
+        - m[4] * m[2] * m[13] - m[12] * m[1] * m[6] + m[12] * m[2] * m[5];
+    inv[3] = -m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11]
+        - m[5] * m[3] * m[10] - m[9] * m[2] * m[7] + m[9] * m[3] * m[6];
+    inv[7] = m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11]
+        + m[4] * m[3] * m[10] + m[8] * m[2] * m[7] - m[8] * m[3] * m[6];
+    inv[11] = -m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11]
+        - m[4] * m[3] * m[9] - m[8] * m[1] * m[7] + m[8] * m[3] * m[5];
+    inv[15] = m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10]
+        + m[4] * m[2] * m[9] + m[8] * m[1] * m[6] - m[8] * m[2] * m[5];
+
+    inv
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, other: Mat4) -> Mat4 {
+        Mat4::from_cols(
+            self * other.cols[0],
+            self * other.cols[1],
+            self * other.cols[2],
+            self * other.cols[3],
+        )
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    fn mul(self, vector: Vec4) -> Vec4 {
+        self.cols[0] * vector.x + self.cols[1] * vector.y + self.cols[2] * vector.z + self.cols[3] * vector.w
+    }
+}
+
+impl Mul<f32> for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, scalar: f32) -> Mat4 {
+        Mat4::from_cols(self.cols[0] * scalar, self.cols[1] * scalar, self.cols[2] * scalar, self.cols[3] * scalar)
+    }
+}
+
+impl MulAssign for Mat4 {
+    fn mul_assign(&mut self, other: Mat4) {
+        *self = *self * other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3, tolerance: f32) {
+        assert!((a - b).length() <= tolerance * b.length().max(1.0), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn mat3_inverse() {
+        let matrix = Mat3::from_scale_rotation_translation(Vec2::new(2.0, -3.0), 0.7, Vec2::new(5.0, -1.0));
+        assert!((matrix * matrix.inverse().unwrap()).approx_eq(Mat3::IDENTITY, 1e-5));
+
+        // tiny scales are still invertible
+        let tiny = Mat3::from_scale(Vec2::splat(1.0 / 4000.0));
+        assert!(tiny.determinant() < 1e-6);
+        let inverse = tiny.inverse().unwrap();
+        assert!((inverse.transform_point(Vec2::splat(0.25)) - Vec2::splat(1000.0)).length() < 1e-2);
+
+        assert_eq!(Mat3::from_scale(Vec2::new(1.0, 0.0)).inverse(), None);
+        let x = Vec3::new(1.0, 2.0, 0.0);
+        let y = Vec3::new(-3.0, 0.5, 0.0);
+        assert_eq!(Mat3::from_cols(x, y, x + y).inverse(), None);
+        assert_eq!(Mat3::from_scale(Vec2::splat(f32::NAN)).inverse(), None);
+    }
+
+    #[test]
+    fn mat4_inverse() {
+        let matrix = Mat4::from_translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::from_axis_angle(Vec3::new(1.0, 1.0, 0.0).normalize(), 1.2)
+            * Mat4::from_scale(Vec3::new(2.0, 0.5, 3.0));
+        assert!((matrix * matrix.inverse().unwrap()).approx_eq(Mat4::IDENTITY, 1e-5));
+        let tiny = Mat4::from_scale(Vec3::splat(1e-3));
+        assert!((tiny * tiny.inverse().unwrap()).approx_eq(Mat4::IDENTITY, 1e-5));
+
+        assert_eq!(Mat4::from_scale(Vec3::new(1.0, 1.0, 0.0)).inverse(), None);
+        let mut flat = Mat4::from_axis_angle(Vec3::Z, 0.3);
+        flat.cols[2] = flat.cols[0] * 2.0 - flat.cols[1];
+        assert_eq!(flat.inverse(), None);
+    }
+
+    #[test]
+    fn orthographic_round_trip() {
+        // pixel space, with a determinant around 1e-9
+        let projection = Mat4::orthographic(0.0, 1920.0, 0.0, 1080.0, -1000.0, 1000.0);
+        assert!(projection.determinant().abs() < 1e-8);
+        let inverse = projection.inverse().unwrap();
+
+        for point in [Vec3::new(0.0, 0.0, 0.0), Vec3::new(1920.0, 1080.0, 500.0), Vec3::new(123.0, 456.0, -999.0)] {
+            let clip = projection.transform_point(point);
+            assert!(clip.x.abs() <= 1.0 + 1e-5 && clip.y.abs() <= 1.0 + 1e-5 && (0.0..=1.0).contains(&clip.z));
+            assert_close(inverse.transform_point(clip), point, 1e-4);
+        }
+        assert_close(projection.transform_point(Vec3::new(0.0, 1080.0, 0.0)), Vec3::new(-1.0, -1.0, 0.5), 1e-5);
+    }
+
+    #[test]
+    fn perspective_round_trip() {
+        let projection = Mat4::perspective(1.0, 16.0 / 9.0, 0.1, 100.0);
+        let inverse = projection.inverse().unwrap();
+
+        for point in [Vec3::new(0.0, 0.0, -1.0), Vec3::new(3.0, -2.0, -10.0), Vec3::new(-30.0, 20.0, -90.0)] {
+            let clip = projection.transform_point(point);
+            assert!((0.0..=1.0).contains(&clip.z));
+            assert_close(inverse.transform_point(clip), point, 1e-3);
+        }
+
+        // the near and far planes map to depth 0 and 1
+        assert!(projection.transform_point(Vec3::new(0.0, 0.0, -0.1)).z.abs() < 1e-5);
+        assert!((projection.transform_point(Vec3::new(0.0, 0.0, -100.0)).z - 1.0).abs() < 1e-5);
+    }
+}
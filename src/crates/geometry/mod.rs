@@ -0,0 +1,108 @@
+#[allow(dead_code)]
+pub mod collision;
+#[allow(dead_code)]
+mod colour;
+#[allow(dead_code)]
+mod gradient;
+#[allow(dead_code)]
+mod matrix;
+#[allow(dead_code)]
+mod path;
+#[allow(dead_code)]
+pub mod polygon;
+#[allow(dead_code)]
+mod rect;
+#[allow(dead_code)]
+pub mod shapes;
+#[allow(dead_code)]
+pub mod stroke;
+#[allow(dead_code)]
+pub mod spatial;
+#[allow(dead_code)]
+mod svg;
+#[allow(dead_code)]
+mod transform;
+#[allow(dead_code)]
+mod triangulate;
+#[allow(dead_code)]
+mod vector;
+#[allow(dead_code)]
+mod vertex;
+
+use bytemuck::{Pod, Zeroable};
+
+use std::ops::{Add, Div, Mul, Sub};
+
+#[allow(unused_imports)]
+pub use colour::Colour;
+#[allow(unused_imports)]
+pub use gradient::{subdivide, ColourRamp, Gradient, GradientShape, Interpolation, Spread};
+#[allow(unused_imports)]
+pub use matrix::{Mat3, Mat4};
+#[allow(unused_imports)]
+pub use path::{Path, PathCommand, SubPath};
+#[allow(unused_imports)]
+pub use rect::{Rect, QUAD_INDICES};
+#[allow(unused_imports)]
+pub use svg::{load_svg, parse_colour, parse_path_data, parse_svg, SvgError, SvgMesh};
+#[allow(unused_imports)]
+pub use transform::Transform2D;
+#[allow(unused_imports)]
+pub use triangulate::{triangulate, triangulate_mesh};
+#[allow(unused_imports)]
+pub use vector::{Vec2, Vec3, Vec4, EPSILON};
+#[allow(unused_imports)]
+pub use vertex::{ColouredTexturedVertex, NormalVertex, TexturedVertex, Vertex3D};
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct Vertex {
+    pub position: Vec2,
+    pub colour: Colour,
+}
+
+vulkano::impl_vertex!(Vertex, position, colour);
+
+impl Vertex {
+    #[allow(dead_code)]
+    pub fn new(position: Vec2, colour: Colour) -> Vertex {
+        Vertex { position, colour }
+    }
+
+    #[allow(dead_code)]
+    pub fn lerp(self, vertex: Vertex, t: f32) -> Vertex {
+        Vertex { position: self.position.lerp(vertex.position, t), colour: self.colour.lerp(vertex.colour, t) }
+    }
+}
+
+impl Add for Vertex {
+    type Output = Vertex;
+
+    fn add(self, vertex: Vertex) -> Vertex {
+        Vertex { position: self.position + vertex.position, colour: self.colour + vertex.colour }
+    }
+}
+
+impl Sub for Vertex {
+    type Output = Vertex;
+
+    fn sub(self, vertex: Vertex) -> Vertex {
+        Vertex { position: self.position - vertex.position, colour: self.colour - vertex.colour }
+    }
+}
+
+impl Mul for Vertex {
+    type Output = Vertex;
+
+    fn mul(self, vertex: Vertex) -> Vertex {
+        Vertex { position: self.position * vertex.position, colour: self.colour * vertex.colour }
+    }
+}
+
+impl Div for Vertex {
+    type Output = Vertex;
+
+    fn div(self, vertex: Vertex) -> Vertex {
+        Vertex { position: self.position / vertex.position, colour: self.colour / vertex.colour }
+    }
+}
@@ -0,0 +1,367 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use super::colour::Colour;
+use super::matrix::Mat3;
+use super::polygon;
+use super::rect::Rect;
+use super::shapes::{MAX_SEGMENTS, MIN_TOLERANCE};
+use super::stroke::{stroke, StrokeStyle};
+use super::triangulate::triangulate_mesh;
+use super::vector::Vec2;
+use super::Vertex;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PathCommand {
+    MoveTo(Vec2),
+    LineTo(Vec2),
+    QuadTo { control: Vec2, to: Vec2 },
+    CubicTo { control1: Vec2, control2: Vec2, to: Vec2 },
+    Close,
+}
+
+/// A flattened piece of a path between two `move_to`s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubPath {
+    pub points: Vec<Vec2>,
+    pub closed: bool,
+}
+
+/// Vector outline made of lines and Bézier curves, flattened into polylines for filling and stroking.
+///
+/// Arcs are stored as cubic curves so every command survives `transform`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Path {
+    commands: Vec<PathCommand>,
+    start: Vec2,
+    current: Vec2,
+}
+
+impl Path {
+    pub fn new() -> Path {
+        Path::default()
+    }
+
+    /// Path through every point, closed back to the first when `closed` is set.
+    pub fn from_points(points: &[Vec2], closed: bool) -> Path {
+        let mut path = Path::new();
+        if let Some((first, rest)) = points.split_first() {
+            path.move_to(*first);
+            for point in rest {
+                path.line_to(*point);
+            }
+            if closed {
+                path.close();
+            }
+        }
+        path
+    }
+
+    pub fn commands(&self) -> &[PathCommand] {
+        &self.commands
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// End point of the last command, where the next one starts from.
+    pub fn current_point(&self) -> Vec2 {
+        self.current
+    }
+
+    pub fn move_to(&mut self, to: Vec2) -> &mut Path {
+        self.commands.push(PathCommand::MoveTo(to));
+        self.start = to;
+        self.current = to;
+        self
+    }
+
+    pub fn line_to(&mut self, to: Vec2) -> &mut Path {
+        self.commands.push(PathCommand::LineTo(to));
+        self.current = to;
+        self
+    }
+
+    pub fn quad_to(&mut self, control: Vec2, to: Vec2) -> &mut Path {
+        self.commands.push(PathCommand::QuadTo { control, to });
+        self.current = to;
+        self
+    }
+
+    pub fn cubic_to(&mut self, control1: Vec2, control2: Vec2, to: Vec2) -> &mut Path {
+        self.commands.push(PathCommand::CubicTo { control1, control2, to });
+        self.current = to;
+        self
+    }
+
+    /// Elliptical arc to `to` using the same parameters as the SVG `A` command.
+    ///
+    /// Out of range radii are scaled up until the arc fits, and zero radii draw a straight line.
+    pub fn arc_to(&mut self, radii: Vec2, x_rotation: f32, large_arc: bool, sweep: bool, to: Vec2) -> &mut Path {
+        let from = self.current;
+        if from == to {
+            return self;
+        }
+
+        let mut radii = radii.abs();
+        if radii.x == 0.0 || radii.y == 0.0 {
+            return self.line_to(to);
+        }
+
+        // convert from endpoints to a center and angles, as described in the SVG implementation notes
+        let half = ((from - to) * 0.5).rotate(-x_rotation);
+        let lambda = (half.x * half.x) / (radii.x * radii.x) + (half.y * half.y) / (radii.y * radii.y);
+        if lambda > 1.0 {
+            radii *= lambda.sqrt();
+        }
+
+        let (rx2, ry2) = (radii.x * radii.x, radii.y * radii.y);
+        let (hx2, hy2) = (half.x * half.x, half.y * half.y);
+        let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+        let coefficient = sign * ((rx2 * ry2 - rx2 * hy2 - ry2 * hx2) / (rx2 * hy2 + ry2 * hx2)).max(0.0).sqrt();
+        let center_prime = Vec2::new(radii.x * half.y / radii.y, -radii.y * half.x / radii.x) * coefficient;
+        let center = center_prime.rotate(x_rotation) + (from + to) * 0.5;
+
+        let start_vector = (half - center_prime) / radii;
+        let end_vector = (-half - center_prime) / radii;
+        let start_angle = start_vector.angle();
+        let mut sweep_angle = start_vector.cross(end_vector).atan2(start_vector.dot(end_vector));
+        if sweep && sweep_angle < 0.0 {
+            sweep_angle += TAU;
+        } else if !sweep && sweep_angle > 0.0 {
+            sweep_angle -= TAU;
+        }
+
+        self.elliptical_arc(center, radii, x_rotation, start_angle, sweep_angle);
+        self.current = to;
+        self
+    }
+
+    /// Circular arc around `center`, joined to the current point with a line, or starting a new sub path if the
+    /// path is empty.
+    pub fn arc(&mut self, center: Vec2, radius: f32, start_angle: f32, sweep: f32) -> &mut Path {
+        let start = center + Vec2::from_angle(start_angle) * radius;
+        if self.commands.is_empty() {
+            self.move_to(start);
+        } else {
+            self.line_to(start);
+        }
+
+        self.elliptical_arc(center, Vec2::splat(radius), 0.0, start_angle, sweep);
+        self
+    }
+
+    pub fn close(&mut self) -> &mut Path {
+        self.commands.push(PathCommand::Close);
+        self.current = self.start;
+        self
+    }
+
+    // approximates the arc with one cubic per quarter turn
+    fn elliptical_arc(&mut self, center: Vec2, radii: Vec2, rotation: f32, start_angle: f32, sweep: f32) {
+        let count = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as u32;
+        let step = sweep / count as f32;
+        let handle = 4.0 / 3.0 * (step / 4.0).tan();
+        let map = |unit: Vec2| center + (unit * radii).rotate(rotation);
+
+        for i in 0..count {
+            let (a, b) = (start_angle + step * i as f32, start_angle + step * (i + 1) as f32);
+            let (from, to) = (Vec2::from_angle(a), Vec2::from_angle(b));
+            self.cubic_to(
+                map(from + from.perpendicular() * handle),
+                map(to - to.perpendicular() * handle),
+                map(to),
+            );
+        }
+    }
+
+    pub fn transform(&self, matrix: Mat3) -> Path {
+        let point = |point: Vec2| matrix.transform_point(point);
+        let commands = self
+            .commands
+            .iter()
+            .map(|command| match *command {
+                PathCommand::MoveTo(to) => PathCommand::MoveTo(point(to)),
+                PathCommand::LineTo(to) => PathCommand::LineTo(point(to)),
+                PathCommand::QuadTo { control, to } => PathCommand::QuadTo { control: point(control), to: point(to) },
+                PathCommand::CubicTo { control1, control2, to } => PathCommand::CubicTo {
+                    control1: point(control1),
+                    control2: point(control2),
+                    to: point(to),
+                },
+                PathCommand::Close => PathCommand::Close,
+            })
+            .collect();
+
+        Path { commands, start: point(self.start), current: point(self.current) }
+    }
+
+    /// Appends every command of `other`.
+    pub fn extend(&mut self, other: &Path) {
+        self.commands.extend_from_slice(&other.commands);
+        self.start = other.start;
+        self.current = other.current;
+    }
+
+    /// Splits curves into lines no further than `tolerance` from the curve, which is clamped to `MIN_TOLERANCE`.
+    pub fn flatten(&self, tolerance: f32) -> Vec<SubPath> {
+        let mut sub_paths = Vec::new();
+        let mut points: Vec<Vec2> = Vec::new();
+        let mut start = Vec2::ZERO;
+        let mut current = Vec2::ZERO;
+
+        for command in &self.commands {
+            if points.is_empty() && !matches!(command, PathCommand::MoveTo(_) | PathCommand::Close) {
+                points.push(current);
+            }
+
+            match *command {
+                PathCommand::MoveTo(to) => {
+                    finish(&mut sub_paths, &mut points, false);
+                    points.push(to);
+                    start = to;
+                    current = to;
+                }
+                PathCommand::LineTo(to) => {
+                    points.push(to);
+                    current = to;
+                }
+                PathCommand::QuadTo { control, to } => {
+                    flatten_quad(&mut points, current, control, to, tolerance);
+                    current = to;
+                }
+                PathCommand::CubicTo { control1, control2, to } => {
+                    flatten_cubic(&mut points, current, control1, control2, to, tolerance);
+                    current = to;
+                }
+                PathCommand::Close => {
+                    finish(&mut sub_paths, &mut points, true);
+                    current = start;
+                }
+            }
+        }
+
+        finish(&mut sub_paths, &mut points, false);
+        sub_paths
+    }
+
+    pub fn bounding_box(&self, tolerance: f32) -> Option<Rect> {
+        let points: Vec<Vec2> = self.flatten(tolerance).into_iter().flat_map(|sub_path| sub_path.points).collect();
+        Rect::from_points(&points)
+    }
+
+    /// Fills the inside of the path using the even-odd rule, treating every sub path as closed.
+    ///
+    /// Sub paths that cross each other are not supported and are left out.
+    pub fn fill(&self, tolerance: f32, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+        let rings: Vec<Vec<Vec2>> = self
+            .flatten(tolerance)
+            .into_iter()
+            .map(|sub_path| sub_path.points)
+            .filter(|points| points.len() >= 3)
+            .collect();
+
+        // how many other rings each ring sits inside, rings at an odd depth are holes
+        let depths: Vec<usize> = rings
+            .iter()
+            .enumerate()
+            .map(|(i, ring)| {
+                rings
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, other)| i != *j && polygon::contains_point(other, ring[0]))
+                    .count()
+            })
+            .collect();
+
+        let mut mesh = (Vec::new(), Vec::new());
+        for (i, outline) in rings.iter().enumerate().filter(|(i, _)| depths[*i].is_multiple_of(2)) {
+            let holes: Vec<Vec<Vec2>> = rings
+                .iter()
+                .enumerate()
+                .filter(|(j, hole)| depths[*j] == depths[i] + 1 && polygon::contains_point(outline, hole[0]))
+                .map(|(_, hole)| hole.clone())
+                .collect();
+
+            if let Some(part) = triangulate_mesh(outline, &holes, colour) {
+                append(&mut mesh, part);
+            }
+        }
+
+        mesh
+    }
+
+    pub fn stroke(&self, tolerance: f32, style: &StrokeStyle, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+        let mut mesh = (Vec::new(), Vec::new());
+        for sub_path in self.flatten(tolerance) {
+            append(&mut mesh, stroke(&sub_path.points, sub_path.closed, style, colour));
+        }
+        mesh
+    }
+}
+
+fn finish(sub_paths: &mut Vec<SubPath>, points: &mut Vec<Vec2>, closed: bool) {
+    let mut points = std::mem::take(points);
+    if closed && points.len() > 1 && points[0] == points[points.len() - 1] {
+        points.pop();
+    }
+    if points.len() > 1 {
+        sub_paths.push(SubPath { points, closed });
+    }
+}
+
+pub(super) fn append(mesh: &mut (Vec<Vertex>, Vec<u32>), part: (Vec<Vertex>, Vec<u32>)) {
+    let offset = mesh.0.len() as u32;
+    mesh.0.extend(part.0);
+    mesh.1.extend(part.1.into_iter().map(|index| index + offset));
+}
+
+// segment counts follow Wang's formula, the bound on how far a Bézier curve strays from its chords
+fn segment_count(scaled_deviation: f32, tolerance: f32) -> u32 {
+    let count = (scaled_deviation / tolerance.max(MIN_TOLERANCE)).sqrt().ceil();
+    count.clamp(1.0, MAX_SEGMENTS as f32) as u32
+}
+
+fn flatten_quad(points: &mut Vec<Vec2>, from: Vec2, control: Vec2, to: Vec2, tolerance: f32) {
+    let deviation = (from - control * 2.0 + to).length();
+    let count = segment_count(deviation / 4.0, tolerance);
+
+    for i in 1..=count {
+        let t = i as f32 / count as f32;
+        let mt = 1.0 - t;
+        points.push(from * (mt * mt) + control * (2.0 * mt * t) + to * (t * t));
+    }
+}
+
+fn flatten_cubic(points: &mut Vec<Vec2>, from: Vec2, control1: Vec2, control2: Vec2, to: Vec2, tolerance: f32) {
+    let deviation = (from - control1 * 2.0 + control2).length().max((control1 - control2 * 2.0 + to).length());
+    let count = segment_count(3.0 * deviation / 4.0, tolerance);
+
+    for i in 1..=count {
+        let t = i as f32 / count as f32;
+        let mt = 1.0 - t;
+        points.push(
+            from * (mt * mt * mt) + control1 * (3.0 * mt * mt * t) + control2 * (3.0 * mt * t * t) + to * (t * t * t),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_positive_tolerance_is_clamped() {
+        let mut path = Path::new();
+        path.move_to(Vec2::ZERO)
+            .quad_to(Vec2::new(5.0, 10.0), Vec2::new(10.0, 0.0))
+            .cubic_to(Vec2::new(12.0, -5.0), Vec2::new(18.0, 5.0), Vec2::new(20.0, 0.0));
+
+        let finest = path.flatten(MIN_TOLERANCE)[0].points.len();
+        for tolerance in [0.0, -1.0, f32::NAN] {
+            assert_eq!(path.flatten(tolerance)[0].points.len(), finest);
+        }
+        assert!(path.bounding_box(0.0).is_some());
+        assert!(!path.stroke(0.0, &StrokeStyle::new(1.0), Colour::WHITE).1.is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+use super::rect::Rect;
+use super::vector::{Vec2, EPSILON};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Winding {
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Shoelace area, positive for counter-clockwise polygons with y pointing up.
+pub fn signed_area(points: &[Vec2]) -> f32 {
+    edges(points).map(|(a, b)| a.cross(b)).sum::<f32>() * 0.5
+}
+
+pub fn area(points: &[Vec2]) -> f32 {
+    signed_area(points).abs()
+}
+
+/// `None` for polygons with no area, such as fewer than three or collinear points.
+pub fn winding(points: &[Vec2]) -> Option<Winding> {
+    let area = signed_area(points);
+    if area > EPSILON {
+        Some(Winding::CounterClockwise)
+    } else if area < -EPSILON {
+        Some(Winding::Clockwise)
+    } else {
+        None
+    }
+}
+
+/// Arithmetic mean of the points.
+pub fn mean(points: &[Vec2]) -> Option<Vec2> {
+    if points.is_empty() {
+        return None;
+    }
+
+    let sum = points.iter().fold(Vec2::ZERO, |sum, point| sum + *point);
+    Some(sum / points.len() as f32)
+}
+
+/// Area-weighted centre of mass of the polygon.
+///
+/// Falls back to the mean for polygons with no area so that lines and single points still get a centre.
+pub fn centroid(points: &[Vec2]) -> Option<Vec2> {
+    let area = signed_area(points);
+    if area.abs() <= EPSILON {
+        return mean(points);
+    }
+
+    // offsetting by the first point keeps the cross products small for polygons far from the origin
+    let origin = points[0];
+    let sum = edges(points).fold(Vec2::ZERO, |sum, (a, b)| {
+        let (a, b) = (a - origin, b - origin);
+        sum + (a + b) * a.cross(b)
+    });
+
+    Some(origin + sum / (6.0 * area))
+}
+
+pub fn bounding_box(points: &[Vec2]) -> Option<Rect> {
+    Rect::from_points(points)
+}
+
+/// Even-odd point in polygon test, points exactly on an edge may land on either side.
+pub fn contains_point(points: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(points) {
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+pub fn is_convex(points: &[Vec2]) -> bool {
+    if points.len() < 3 {
+        return false;
+    }
+
+    let mut sign = 0.0;
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        let c = points[(i + 2) % points.len()];
+
+        let turn = (b - a).cross(c - b);
+        if turn.abs() <= EPSILON {
+            continue;
+        }
+        if sign != 0.0 && turn.signum() != sign {
+            return false;
+        }
+        sign = turn.signum();
+    }
+
+    sign != 0.0
+}
+
+/// Convex hull in counter-clockwise order without collinear points, using Andrew's monotone chain.
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut lower = half_hull(sorted.iter());
+    let mut upper = half_hull(sorted.iter().rev());
+
+    // each half ends on the point the other one starts with
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+fn half_hull<'a>(points: impl Iterator<Item = &'a Vec2>) -> Vec<Vec2> {
+    let mut hull: Vec<Vec2> = Vec::new();
+
+    for point in points {
+        while hull.len() >= 2 && (hull[hull.len() - 1] - hull[hull.len() - 2]).cross(*point - hull[hull.len() - 1]) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(*point);
+    }
+
+    hull
+}
+
+/// Reverses the points in place if they are not already in the requested winding.
+pub fn set_winding(points: &mut [Vec2], winding: Winding) {
+    if let Some(current) = self::winding(points) {
+        if current != winding {
+            points.reverse();
+        }
+    }
+}
+
+/// Every edge of the closed polygon as a pair of points.
+pub fn edges(points: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    points.iter().enumerate().map(move |(i, point)| (*point, points[(i + 1) % points.len()]))
+}
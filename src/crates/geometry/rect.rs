@@ -0,0 +1,156 @@
+use super::colour::Colour;
+use super::vector::Vec2;
+use super::vertex::TexturedVertex;
+use super::Vertex;
+
+/// Indices drawing `Rect::corners` as two triangles sharing the diagonal.
+pub const QUAD_INDICES: [u32; 6] = [0, 1, 2, 2, 3, 0];
+
+/// Axis-aligned rectangle, `min` is the bottom left corner and `max` the top right.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    /// From (0, 0) to (1, 1), which as texture coordinates is the whole texture.
+    pub const UNIT: Rect = Rect { min: Vec2::ZERO, max: Vec2::ONE };
+
+    /// Creates a rect from any two opposite corners.
+    pub fn new(a: Vec2, b: Vec2) -> Rect {
+        Rect { min: a.min(b), max: a.max(b) }
+    }
+
+    pub fn from_min_size(min: Vec2, size: Vec2) -> Rect {
+        Rect::new(min, min + size)
+    }
+
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Rect {
+        let half_size = size.abs() * 0.5;
+        Rect { min: center - half_size, max: center + half_size }
+    }
+
+    /// Smallest rect containing every point, `None` for no points.
+    pub fn from_points(points: &[Vec2]) -> Option<Rect> {
+        let (first, rest) = points.split_first()?;
+        Some(rest.iter().fold(Rect { min: *first, max: *first }, |rect, point| rect.including(*point)))
+    }
+
+    pub fn width(self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn size(self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn center(self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn area(self) -> f32 {
+        self.width() * self.height()
+    }
+
+    /// Width over height, `None` for a rect with no height.
+    pub fn aspect_ratio(self) -> Option<f32> {
+        if self.height() == 0.0 { None } else { Some(self.width() / self.height()) }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.width() <= 0.0 || self.height() <= 0.0
+    }
+
+    /// Points on the edge count as inside.
+    pub fn contains_point(self, point: Vec2) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x && point.y >= self.min.y && point.y <= self.max.y
+    }
+
+    pub fn contains_rect(self, other: Rect) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    /// Rects that only share an edge count as intersecting.
+    pub fn intersects(self, other: Rect) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x && self.min.y <= other.max.y && self.max.y >= other.min.y
+    }
+
+    /// Overlapping area of both rects, `None` if they do not intersect.
+    pub fn intersection(self, other: Rect) -> Option<Rect> {
+        if self.intersects(other) {
+            Some(Rect { min: self.min.max(other.min), max: self.max.min(other.max) })
+        } else {
+            None
+        }
+    }
+
+    pub fn union(self, other: Rect) -> Rect {
+        Rect { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    /// Grows the rect so that it contains `point`.
+    pub fn including(self, point: Vec2) -> Rect {
+        Rect { min: self.min.min(point), max: self.max.max(point) }
+    }
+
+    /// Grows every side by `amount`, a negative amount shrinks the rect down to its center.
+    pub fn expand(self, amount: f32) -> Rect {
+        self.expand_by(Vec2::splat(amount))
+    }
+
+    pub fn expand_by(self, amount: Vec2) -> Rect {
+        let center = self.center();
+        Rect { min: (self.min - amount).min(center), max: (self.max + amount).max(center) }
+    }
+
+    pub fn translate(self, offset: Vec2) -> Rect {
+        Rect { min: self.min + offset, max: self.max + offset }
+    }
+
+    pub fn clamp_point(self, point: Vec2) -> Vec2 {
+        point.max(self.min).min(self.max)
+    }
+
+    /// Top left, top right, bottom right, bottom left, to be drawn with `QUAD_INDICES`.
+    pub fn corners(self) -> [Vec2; 4] {
+        [
+            Vec2::new(self.min.x, self.max.y),
+            self.max,
+            Vec2::new(self.max.x, self.min.y),
+            self.min,
+        ]
+    }
+
+    /// The corners as vertices, coloured in the same order as `corners`.
+    pub fn to_vertices(self, colours: [Colour; 4]) -> [Vertex; 4] {
+        let corners = self.corners();
+        [0, 1, 2, 3].map(|i| Vertex::new(corners[i], colours[i]))
+    }
+
+    /// Vertices and indices ready for `create_vertex_buffer` and `create_index_buffer`.
+    pub fn to_mesh(self, colours: [Colour; 4]) -> (Vec<Vertex>, Vec<u32>) {
+        (self.to_vertices(colours).to_vec(), QUAD_INDICES.to_vec())
+    }
+
+    /// Texture coordinates for each of `corners`, showing the `uvs` part of a texture upright. Texture coordinates
+    /// grow downwards, so `uvs.min` is the top left of that part.
+    pub fn uv_corners(uvs: Rect) -> [Vec2; 4] {
+        [uvs.min, Vec2::new(uvs.max.x, uvs.min.y), uvs.max, Vec2::new(uvs.min.x, uvs.max.y)]
+    }
+
+    /// The corners as vertices showing the `uvs` part of a texture, see `uv_corners`.
+    pub fn to_textured_vertices(self, uvs: Rect) -> [TexturedVertex; 4] {
+        let corners = self.corners();
+        let uv_corners = Rect::uv_corners(uvs);
+        [0, 1, 2, 3].map(|i| TexturedVertex::new(corners[i], uv_corners[i]))
+    }
+
+    pub fn to_textured_mesh(self, uvs: Rect) -> (Vec<TexturedVertex>, Vec<u32>) {
+        (self.to_textured_vertices(uvs).to_vec(), QUAD_INDICES.to_vec())
+    }
+}
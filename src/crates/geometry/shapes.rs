@@ -0,0 +1,234 @@
+use std::f32::consts::TAU;
+
+use super::colour::Colour;
+use super::rect::Rect;
+use super::vector::Vec2;
+use super::Vertex;
+
+/// How finely curved edges are split into straight segments.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Segments {
+    /// Segments used for a full turn, arcs use a proportional amount.
+    Count(u32),
+    /// Furthest distance allowed between the curve and its segments.
+    Tolerance(f32),
+}
+
+/// Smallest tolerance `Segments` and path flattening use, anything finer is clamped to it.
+pub const MIN_TOLERANCE: f32 = 1e-5;
+
+/// Most segments used for a full turn, however fine the tolerance.
+pub const MAX_SEGMENTS: u32 = 1 << 16;
+
+impl Default for Segments {
+    fn default() -> Segments {
+        Segments::Tolerance(0.001)
+    }
+}
+
+impl Segments {
+    /// Segments needed for an arc of `sweep` radians with the given radius, never fewer than one nor more than
+    /// `MAX_SEGMENTS` for a full turn.
+    pub fn for_arc(self, radius: f32, sweep: f32) -> u32 {
+        let turns = (sweep.abs() / TAU) as f64;
+        let segments = match self {
+            Segments::Count(count) => count as f64 * turns,
+            Segments::Tolerance(tolerance) => {
+                let tolerance = tolerance.max(MIN_TOLERANCE) as f64;
+                let radius = radius.abs() as f64;
+                if radius <= tolerance {
+                    1.0
+                } else {
+                    let step = 2.0 * (1.0 - tolerance / radius).acos();
+                    std::f64::consts::TAU * turns / step
+                }
+            }
+        };
+
+        let most = (MAX_SEGMENTS as f64 * turns).ceil();
+        (segments.ceil().min(most) as u32).max(1)
+    }
+}
+
+/// Points along an elliptical arc, including both ends.
+pub fn ellipse_arc_points(center: Vec2, radii: Vec2, start_angle: f32, sweep: f32, segments: Segments) -> Vec<Vec2> {
+    arc_points(center, radii, start_angle, sweep, segments.for_arc(radii.x.max(radii.y), sweep))
+}
+
+/// Points along an elliptical arc split into exactly `count` segments.
+fn arc_points(center: Vec2, radii: Vec2, start_angle: f32, sweep: f32, count: u32) -> Vec<Vec2> {
+    (0..=count)
+        .map(|i| {
+            let angle = start_angle + sweep * i as f32 / count as f32;
+            center + Vec2::from_angle(angle) * radii
+        })
+        .collect()
+}
+
+/// Points around a full ellipse, counter-clockwise without repeating the first point.
+pub fn ellipse_points(center: Vec2, radii: Vec2, segments: Segments) -> Vec<Vec2> {
+    let mut points = ellipse_arc_points(center, radii, 0.0, TAU, segments);
+    points.pop();
+    points
+}
+
+pub fn regular_polygon_points(center: Vec2, radius: f32, sides: u32, rotation: f32) -> Vec<Vec2> {
+    let sides = sides.max(3);
+    (0..sides)
+        .map(|i| center + Vec2::from_angle(rotation + TAU * i as f32 / sides as f32) * radius)
+        .collect()
+}
+
+/// Alternates between the outer and inner radius, starting with a tip at `rotation`.
+pub fn star_points(center: Vec2, outer_radius: f32, inner_radius: f32, tips: u32, rotation: f32) -> Vec<Vec2> {
+    let count = tips.max(2) * 2;
+    (0..count)
+        .map(|i| {
+            let radius = if i % 2 == 0 { outer_radius } else { inner_radius };
+            center + Vec2::from_angle(rotation + TAU * i as f32 / count as f32) * radius
+        })
+        .collect()
+}
+
+/// Outline of a rect with every corner rounded by `radius`, clamped to half the shorter side.
+pub fn rounded_rect_points(rect: Rect, radius: f32, segments: Segments) -> Vec<Vec2> {
+    let radius = radius.max(0.0).min(rect.width().min(rect.height()) * 0.5);
+    if radius == 0.0 {
+        let mut corners = rect.corners();
+        corners.reverse();
+        return corners.to_vec();
+    }
+
+    let inner = rect.expand(-radius);
+    let quarter = TAU / 4.0;
+    let corners = [
+        (Vec2::new(inner.max.x, inner.min.y), -quarter),
+        (inner.max, 0.0),
+        (Vec2::new(inner.min.x, inner.max.y), quarter),
+        (inner.min, 2.0 * quarter),
+    ];
+
+    corners
+        .iter()
+        .flat_map(|(center, start)| ellipse_arc_points(*center, Vec2::splat(radius), *start, quarter, segments))
+        .collect()
+}
+
+pub fn circle(center: Vec2, radius: f32, segments: Segments, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    ellipse(center, Vec2::splat(radius), segments, colour)
+}
+
+pub fn ellipse(center: Vec2, radii: Vec2, segments: Segments, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    fan(center, &ellipse_points(center, radii, segments), true, colour)
+}
+
+pub fn regular_polygon(center: Vec2, radius: f32, sides: u32, rotation: f32, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    fan(center, &regular_polygon_points(center, radius, sides, rotation), true, colour)
+}
+
+pub fn star(center: Vec2, outer_radius: f32, inner_radius: f32, tips: u32, rotation: f32, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    fan(center, &star_points(center, outer_radius, inner_radius, tips, rotation), true, colour)
+}
+
+pub fn rounded_rect(rect: Rect, radius: f32, segments: Segments, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    fan(rect.center(), &rounded_rect_points(rect, radius, segments), true, colour)
+}
+
+/// Pie slice from `start_angle` sweeping counter-clockwise, or clockwise for a negative sweep.
+pub fn pie(center: Vec2, radius: f32, start_angle: f32, sweep: f32, segments: Segments, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    let sweep = sweep.clamp(-TAU, TAU);
+    let points = ellipse_arc_points(center, Vec2::splat(radius), start_angle, sweep, segments);
+    fan(center, &points, false, colour)
+}
+
+/// Curved band between two radii, `thickness` wide and centered on `radius`.
+pub fn arc(center: Vec2, radius: f32, thickness: f32, start_angle: f32, sweep: f32, segments: Segments, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    let sweep = sweep.clamp(-TAU, TAU);
+    let half = thickness.abs() * 0.5;
+    // both edges share the outer edge's segments, so every quad spans the same angle
+    let count = segments.for_arc(radius + half, sweep);
+    let outer = arc_points(center, Vec2::splat(radius + half), start_angle, sweep, count);
+    let inner = arc_points(center, Vec2::splat((radius - half).max(0.0)), start_angle, sweep, count);
+    strip(&outer, &inner, false, colour)
+}
+
+pub fn ring(center: Vec2, inner_radius: f32, outer_radius: f32, segments: Segments, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    let count = segments.for_arc(outer_radius.abs().max(inner_radius.abs()), TAU);
+    let mut outer = arc_points(center, Vec2::splat(outer_radius), 0.0, TAU, count);
+    let mut inner = arc_points(center, Vec2::splat(inner_radius), 0.0, TAU, count);
+    outer.pop();
+    inner.pop();
+    strip(&outer, &inner, true, colour)
+}
+
+/// Triangles from `center` to every edge of `outline`, for shapes that are star-shaped around their center.
+pub fn fan(center: Vec2, outline: &[Vec2], closed: bool, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::with_capacity(outline.len() + 1);
+    vertices.push(Vertex::new(center, colour));
+    vertices.extend(outline.iter().map(|point| Vertex::new(*point, colour)));
+
+    let count = outline.len() as u32;
+    let edges = if closed { count } else { count.saturating_sub(1) };
+    let indices = (0..edges).flat_map(|i| [0, i + 1, (i + 1) % count + 1]).collect();
+
+    (vertices, indices)
+}
+
+/// Quads joining two outlines with the same number of points.
+pub fn strip(a: &[Vec2], b: &[Vec2], closed: bool, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    assert_eq!(a.len(), b.len(), "outlines of a strip need the same number of points");
+    let count = a.len() as u32;
+    let vertices = a
+        .iter()
+        .chain(b.iter())
+        .map(|point| Vertex::new(*point, colour))
+        .collect();
+
+    let edges = if closed { count } else { count.saturating_sub(1) };
+    let indices = (0..edges)
+        .flat_map(|i| {
+            let next = (i + 1) % count;
+            [i, next, count + next, count + next, count + i, i]
+        })
+        .collect();
+
+    (vertices, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_tolerance_is_clamped() {
+        let segments = Segments::Tolerance(0.0).for_arc(1.0, TAU);
+        assert_eq!(segments, Segments::Tolerance(MIN_TOLERANCE).for_arc(1.0, TAU));
+        assert!(segments <= MAX_SEGMENTS);
+        assert!(Segments::Tolerance(-1.0).for_arc(1.0, TAU) <= MAX_SEGMENTS);
+        assert!(Segments::Tolerance(0.0).for_arc(1.0e6, TAU) <= MAX_SEGMENTS);
+        assert_eq!(Segments::Tolerance(0.0).for_arc(1.0e6, TAU / 2.0), MAX_SEGMENTS / 2);
+    }
+
+    #[test]
+    fn ring_sides_match() {
+        let (vertices, indices) = ring(Vec2::ZERO, 1.0, 10.0, Segments::default(), Colour::WHITE);
+        let count = vertices.len() / 2;
+        assert_eq!(indices.len(), count * 6);
+
+        // every outer point lines up with the inner point at the same angle
+        for i in 0..count {
+            let outer = vertices[i].position;
+            let inner = vertices[count + i].position;
+            assert!((outer.normalize() - inner.normalize()).length() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn arc_sides_match() {
+        let (vertices, indices) = arc(Vec2::ZERO, 5.0, 4.0, 0.0, TAU / 3.0, Segments::default(), Colour::WHITE);
+        let count = vertices.len() / 2;
+        assert_eq!(indices.len(), (count - 1) * 6);
+        assert!((vertices[count - 1].position.angle() - TAU / 3.0).abs() < 1e-4);
+        assert!((vertices[2 * count - 1].position.angle() - TAU / 3.0).abs() < 1e-4);
+    }
+}
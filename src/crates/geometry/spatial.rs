@@ -0,0 +1,768 @@
+use std::collections::HashMap;
+
+use super::rect::Rect;
+use super::vector::Vec2;
+
+/// Refers to an item in a `QuadTree` or `SpatialHash`.
+///
+/// Handles of removed items are never reused, so a stale handle simply finds nothing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Handle {
+    index: u32,
+    generation: u32,
+}
+
+/// Grid of equally sized cells, with every item listed in each cell its bounds touch.
+///
+/// Works best when items are roughly the size of a cell and spread over an unbounded area.
+#[derive(Debug, Clone)]
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<u32>>,
+    items: Slots<(Rect, T)>,
+    // cells that have ever held an item, so nearest neighbour searches know when to give up
+    extent: Option<((i32, i32), (i32, i32))>,
+}
+
+/// Tree of rects split into quarters once they hold too many items.
+///
+/// Items that straddle a split stay in the parent, and items outside the root bounds are kept in the root.
+#[derive(Debug, Clone)]
+pub struct QuadTree<T> {
+    nodes: Vec<Node>,
+    items: Slots<QuadItem<T>>,
+    max_items: usize,
+    max_depth: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    bounds: Rect,
+    depth: u32,
+    // index of the first of four consecutive children
+    children: Option<usize>,
+    items: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct QuadItem<T> {
+    bounds: Rect,
+    value: T,
+    node: usize,
+}
+
+impl<T> SpatialHash<T> {
+    pub fn new(cell_size: f32) -> SpatialHash<T> {
+        SpatialHash { cell_size: cell_size.max(f32::EPSILON), cells: HashMap::new(), items: Slots::default(), extent: None }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.items = Slots::default();
+        self.extent = None;
+    }
+
+    pub fn insert(&mut self, bounds: Rect, value: T) -> Handle {
+        let handle = self.items.insert((bounds, value));
+        self.add_to_cells(handle.index, bounds);
+        handle
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let (bounds, value) = self.items.remove(handle)?;
+        self.remove_from_cells(handle.index, bounds);
+        Some(value)
+    }
+
+    /// Moves an item to new bounds, returning `false` for a stale handle.
+    pub fn set_bounds(&mut self, handle: Handle, bounds: Rect) -> bool {
+        let old = match self.items.get_mut(handle) {
+            Some(item) => std::mem::replace(&mut item.0, bounds),
+            None => return false,
+        };
+
+        if self.cell_range(old) != self.cell_range(bounds) {
+            self.remove_from_cells(handle.index, old);
+            self.add_to_cells(handle.index, bounds);
+        }
+        true
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.items.get(handle).map(|item| &item.1)
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.items.get_mut(handle).map(|item| &mut item.1)
+    }
+
+    pub fn bounds(&self, handle: Handle) -> Option<Rect> {
+        self.items.get(handle).map(|item| item.0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, Rect, &T)> + '_ {
+        self.items.iter().map(|(handle, item)| (handle, item.0, &item.1))
+    }
+
+    /// Items whose bounds intersect `rect`.
+    pub fn query_rect(&self, rect: Rect) -> Vec<Handle> {
+        let ((x0, y0), (x1, y1)) = self.cell_range(rect);
+        let mut found: Vec<u32> = Vec::new();
+
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    found.extend(cell.iter().filter(|index| self.items.bounds(**index, |item| item.0).intersects(rect)));
+                }
+            }
+        }
+
+        // items spanning several cells are found once per cell
+        found.sort_unstable();
+        found.dedup();
+        found.into_iter().map(|index| self.items.handle(index)).collect()
+    }
+
+    /// Items whose bounds come within `radius` of `center`.
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<Handle> {
+        let mut found = self.query_rect(Rect::from_center_size(center, Vec2::splat(radius * 2.0)));
+        found.retain(|handle| self.bounds(*handle).is_some_and(|bounds| distance_squared(bounds, center) <= radius * radius));
+        found
+    }
+
+    /// Item with bounds closest to `point`, searching outwards one ring of cells at a time.
+    pub fn nearest(&self, point: Vec2) -> Option<Handle> {
+        let (low, high) = self.extent?;
+        // in i64, so rings around cells at the ends of the i32 range cannot overflow
+        let extent = ((low.0 as i64, low.1 as i64), (high.0 as i64, high.1 as i64));
+        let ((min_x, min_y), (max_x, max_y)) = extent;
+        let (cx, cy) = self.cell(point);
+        let (cx, cy) = (cx as i64, cy as i64);
+
+        // rings before the first one reaching the extent are empty
+        let outside = |c: i64, min: i64, max: i64| (min - c).max(c - max).max(0);
+        let first = outside(cx, min_x, max_x).max(outside(cy, min_y, max_y));
+
+        let mut best: Option<(u32, f32)> = None;
+        for ring in first.. {
+            let (left, right, top, bottom) = (cx - ring, cx + ring, cy - ring, cy + ring);
+
+            // only the border of the ring, clipped to the extent: the top and bottom rows, then the sides between them
+            let rows: &[i64] = if ring == 0 { &[top] } else { &[top, bottom] };
+            for &y in rows.iter().filter(|y| (min_y..=max_y).contains(*y)) {
+                for x in left.max(min_x)..=right.min(max_x) {
+                    self.nearest_in_cell((x, y), point, &mut best);
+                }
+            }
+            for &x in [left, right].iter().filter(|x| ring > 0 && (min_x..=max_x).contains(*x)) {
+                for y in (top + 1).max(min_y)..=(bottom - 1).min(max_y) {
+                    self.nearest_in_cell((x, y), point, &mut best);
+                }
+            }
+
+            match self.distance_past_ring(point, (cx, cy), ring, extent) {
+                Some(rest) if best.is_none_or(|(_, closest)| closest > rest) => {}
+                _ => break,
+            }
+        }
+
+        best.map(|(index, _)| self.items.handle(index))
+    }
+
+    /// Every pair of items whose bounds intersect, each pair reported once.
+    pub fn pairs(&self) -> Vec<(Handle, Handle)> {
+        let mut pairs = Vec::new();
+
+        for (key, cell) in &self.cells {
+            for (i, a) in cell.iter().enumerate() {
+                let bounds_a = self.items.bounds(*a, |item| item.0);
+                for b in &cell[i + 1..] {
+                    let bounds_b = self.items.bounds(*b, |item| item.0);
+                    let overlap = match bounds_a.intersection(bounds_b) {
+                        Some(overlap) => overlap,
+                        None => continue,
+                    };
+
+                    // pairs sharing several cells are only reported from the cell holding the overlap's min corner
+                    if self.cell(overlap.min) == *key {
+                        pairs.push(ordered(self.items.handle(*a), self.items.handle(*b)));
+                    }
+                }
+            }
+        }
+
+        pairs
+    }
+
+    // squared distance from `point` to the cells of the extent outside the rings searched so far, which is as close
+    // as any item not found yet can be, or `None` once the rings cover the whole extent
+    fn distance_past_ring(&self, point: Vec2, center: (i64, i64), ring: i64, extent: ((i64, i64), (i64, i64))) -> Option<f32> {
+        let ((min_x, min_y), (max_x, max_y)) = extent;
+        let (left, right) = ((center.0 - ring).max(min_x), (center.0 + ring).min(max_x));
+        let strips = [
+            ((min_x, min_y), (center.0 - ring - 1, max_y)),
+            ((center.0 + ring + 1, min_y), (max_x, max_y)),
+            ((left, min_y), (right, center.1 - ring - 1)),
+            ((left, center.1 + ring + 1), (right, max_y)),
+        ];
+
+        strips
+            .into_iter()
+            .filter(|(low, high)| low.0 <= high.0 && low.1 <= high.1)
+            .map(|(low, high)| {
+                let corner = |x: i64, y: i64| Vec2::new(x as f32, y as f32) * self.cell_size;
+                distance_squared(Rect::new(corner(low.0, low.1), corner(high.0 + 1, high.1 + 1)), point)
+            })
+            .reduce(f32::min)
+    }
+
+    // cells passed in lie within the extent, so they fit back into i32
+    fn nearest_in_cell(&self, cell: (i64, i64), point: Vec2, best: &mut Option<(u32, f32)>) {
+        for index in self.cells.get(&(cell.0 as i32, cell.1 as i32)).into_iter().flatten() {
+            let distance = distance_squared(self.items.bounds(*index, |item| item.0), point);
+            if best.is_none_or(|(_, closest)| distance < closest) {
+                *best = Some((*index, distance));
+            }
+        }
+    }
+
+    fn cell(&self, point: Vec2) -> (i32, i32) {
+        ((point.x / self.cell_size).floor() as i32, (point.y / self.cell_size).floor() as i32)
+    }
+
+    fn cell_range(&self, bounds: Rect) -> ((i32, i32), (i32, i32)) {
+        (self.cell(bounds.min), self.cell(bounds.max))
+    }
+
+    fn add_to_cells(&mut self, index: u32, bounds: Rect) {
+        let (min, max) = self.cell_range(bounds);
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                self.cells.entry((x, y)).or_default().push(index);
+            }
+        }
+
+        self.extent = Some(match self.extent {
+            Some((low, high)) => ((low.0.min(min.0), low.1.min(min.1)), (high.0.max(max.0), high.1.max(max.1))),
+            None => (min, max),
+        });
+    }
+
+    fn remove_from_cells(&mut self, index: u32, bounds: Rect) {
+        let (min, max) = self.cell_range(bounds);
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(cell) = self.cells.get_mut(&(x, y)) {
+                    cell.retain(|other| *other != index);
+                    if cell.is_empty() {
+                        self.cells.remove(&(x, y));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T> QuadTree<T> {
+    /// Tree covering `bounds`, splitting nodes with more than eight items up to eight levels deep.
+    pub fn new(bounds: Rect) -> QuadTree<T> {
+        QuadTree::with_limits(bounds, 8, 8)
+    }
+
+    pub fn with_limits(bounds: Rect, max_items: usize, max_depth: u32) -> QuadTree<T> {
+        QuadTree {
+            nodes: vec![Node { bounds, depth: 0, children: None, items: Vec::new() }],
+            items: Slots::default(),
+            max_items: max_items.max(1),
+            max_depth,
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.nodes[0].bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        let bounds = self.bounds();
+        self.nodes = vec![Node { bounds, depth: 0, children: None, items: Vec::new() }];
+        self.items = Slots::default();
+    }
+
+    pub fn insert(&mut self, bounds: Rect, value: T) -> Handle {
+        let handle = self.items.insert(QuadItem { bounds, value, node: 0 });
+        self.place(handle.index, 0);
+        handle
+    }
+
+    /// Removes an item, leaving the nodes split so that the tree does not thrash as items come and go.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let item = self.items.remove(handle)?;
+        self.nodes[item.node].items.retain(|index| *index != handle.index);
+        Some(item.value)
+    }
+
+    /// Moves an item to new bounds, returning `false` for a stale handle.
+    pub fn set_bounds(&mut self, handle: Handle, bounds: Rect) -> bool {
+        let node = match self.items.get_mut(handle) {
+            Some(item) => {
+                item.bounds = bounds;
+                item.node
+            }
+            None => return false,
+        };
+
+        // stay put while the item still fits and cannot sink any deeper
+        let fits = node == 0 || self.nodes[node].bounds.contains_rect(bounds);
+        if fits && self.child_for(node, bounds).is_none() {
+            return true;
+        }
+
+        self.nodes[node].items.retain(|index| *index != handle.index);
+        self.place(handle.index, 0);
+        true
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.items.get(handle).map(|item| &item.value)
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.items.get_mut(handle).map(|item| &mut item.value)
+    }
+
+    pub fn item_bounds(&self, handle: Handle) -> Option<Rect> {
+        self.items.get(handle).map(|item| item.bounds)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, Rect, &T)> + '_ {
+        self.items.iter().map(|(handle, item)| (handle, item.bounds, &item.value))
+    }
+
+    /// Items whose bounds intersect `rect`.
+    pub fn query_rect(&self, rect: Rect) -> Vec<Handle> {
+        let mut found = Vec::new();
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            found.extend(
+                node.items
+                    .iter()
+                    .filter(|index| self.items.bounds(**index, |item| item.bounds).intersects(rect))
+                    .map(|index| self.items.handle(*index)),
+            );
+
+            if let Some(first) = node.children {
+                stack.extend((first..first + 4).filter(|child| self.nodes[*child].bounds.intersects(rect)));
+            }
+        }
+
+        found
+    }
+
+    /// Items whose bounds come within `radius` of `center`.
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<Handle> {
+        let mut found = self.query_rect(Rect::from_center_size(center, Vec2::splat(radius * 2.0)));
+        found.retain(|handle| {
+            self.item_bounds(*handle).is_some_and(|bounds| distance_squared(bounds, center) <= radius * radius)
+        });
+        found
+    }
+
+    /// Item with bounds closest to `point`, skipping nodes further away than the best match so far.
+    pub fn nearest(&self, point: Vec2) -> Option<Handle> {
+        let mut best: Option<(u32, f32)> = None;
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            for index in &node.items {
+                let distance = distance_squared(self.items.bounds(*index, |item| item.bounds), point);
+                if best.is_none_or(|(_, closest)| distance < closest) {
+                    best = Some((*index, distance));
+                }
+            }
+
+            if let Some(first) = node.children {
+                let mut children: Vec<(usize, f32)> = (first..first + 4)
+                    .map(|child| (child, distance_squared(self.nodes[child].bounds, point)))
+                    .filter(|(_, distance)| best.is_none_or(|(_, closest)| *distance < closest))
+                    .collect();
+
+                // visit the closest child next so later ones are more likely to be skipped
+                children.sort_by(|a, b| b.1.total_cmp(&a.1));
+                stack.extend(children.into_iter().map(|(child, _)| child));
+            }
+        }
+
+        best.map(|(index, _)| self.items.handle(index))
+    }
+
+    /// Every pair of items whose bounds intersect, each pair reported once.
+    pub fn pairs(&self) -> Vec<(Handle, Handle)> {
+        let mut pairs = Vec::new();
+        let mut ancestors: Vec<u32> = Vec::new();
+        self.collect_pairs(0, &mut ancestors, &mut pairs);
+        pairs
+    }
+
+    // items only need testing against the rest of their node and the nodes above it
+    fn collect_pairs(&self, node: usize, ancestors: &mut Vec<u32>, pairs: &mut Vec<(Handle, Handle)>) {
+        let node = &self.nodes[node];
+        let bounds = |index: u32| self.items.bounds(index, |item| item.bounds);
+
+        for (i, a) in node.items.iter().enumerate() {
+            for b in node.items[i + 1..].iter().chain(ancestors.iter()) {
+                if bounds(*a).intersects(bounds(*b)) {
+                    pairs.push(ordered(self.items.handle(*a), self.items.handle(*b)));
+                }
+            }
+        }
+
+        if let Some(first) = node.children {
+            let count = ancestors.len();
+            ancestors.extend_from_slice(&node.items);
+            for child in first..first + 4 {
+                self.collect_pairs(child, ancestors, pairs);
+            }
+            ancestors.truncate(count);
+        }
+    }
+
+    // pushes the item down from `node` as far as it fits, splitting full leaves on the way
+    fn place(&mut self, index: u32, mut node: usize) {
+        let bounds = self.items.bounds(index, |item| item.bounds);
+        while let Some(child) = self.child_for(node, bounds) {
+            node = child;
+        }
+
+        self.nodes[node].items.push(index);
+        self.items.set_node(index, node);
+
+        let leaf = &self.nodes[node];
+        if leaf.children.is_none() && leaf.items.len() > self.max_items && leaf.depth < self.max_depth {
+            self.split(node);
+        }
+    }
+
+    fn split(&mut self, node: usize) {
+        let Node { bounds, depth, .. } = self.nodes[node];
+        let center = bounds.center();
+        let first = self.nodes.len();
+
+        for (min, max) in [
+            (bounds.min, center),
+            (Vec2::new(center.x, bounds.min.y), Vec2::new(bounds.max.x, center.y)),
+            (Vec2::new(bounds.min.x, center.y), Vec2::new(center.x, bounds.max.y)),
+            (center, bounds.max),
+        ] {
+            self.nodes.push(Node { bounds: Rect { min, max }, depth: depth + 1, children: None, items: Vec::new() });
+        }
+        self.nodes[node].children = Some(first);
+
+        for index in std::mem::take(&mut self.nodes[node].items) {
+            self.place(index, node);
+        }
+    }
+
+    // the child of `node` that wholly contains `bounds`, if there is one
+    fn child_for(&self, node: usize, bounds: Rect) -> Option<usize> {
+        let first = self.nodes[node].children?;
+        (first..first + 4).find(|child| self.nodes[*child].bounds.contains_rect(bounds))
+    }
+}
+
+impl<T> Slots<QuadItem<T>> {
+    fn set_node(&mut self, index: u32, node: usize) {
+        if let Some(item) = self.entries[index as usize].value.as_mut() {
+            item.node = node;
+        }
+    }
+}
+
+// generational storage behind `Handle`s
+#[derive(Debug, Clone)]
+struct Slots<E> {
+    entries: Vec<Slot<E>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Slot<E> {
+    generation: u32,
+    value: Option<E>,
+}
+
+impl<E> Default for Slots<E> {
+    fn default() -> Slots<E> {
+        Slots { entries: Vec::new(), free: Vec::new(), len: 0 }
+    }
+}
+
+impl<E> Slots<E> {
+    fn insert(&mut self, value: E) -> Handle {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.entries[index as usize];
+            slot.value = Some(value);
+            return Handle { index, generation: slot.generation };
+        }
+
+        self.entries.push(Slot { generation: 0, value: Some(value) });
+        Handle { index: self.entries.len() as u32 - 1, generation: 0 }
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<E> {
+        let slot = self.entries.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    fn get(&self, handle: Handle) -> Option<&E> {
+        let slot = self.entries.get(handle.index as usize)?;
+        if slot.generation == handle.generation { slot.value.as_ref() } else { None }
+    }
+
+    fn get_mut(&mut self, handle: Handle) -> Option<&mut E> {
+        let slot = self.entries.get_mut(handle.index as usize)?;
+        if slot.generation == handle.generation { slot.value.as_mut() } else { None }
+    }
+
+    // the current handle of a live index
+    fn handle(&self, index: u32) -> Handle {
+        Handle { index, generation: self.entries[index as usize].generation }
+    }
+
+    // bounds of a live index, read out with `bounds`
+    fn bounds(&self, index: u32, bounds: impl Fn(&E) -> Rect) -> Rect {
+        self.entries[index as usize].value.as_ref().map(bounds).unwrap_or_default()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Handle, &E)> + '_ {
+        self.entries.iter().enumerate().filter_map(|(index, slot)| {
+            slot.value.as_ref().map(|value| (Handle { index: index as u32, generation: slot.generation }, value))
+        })
+    }
+}
+
+fn distance_squared(bounds: Rect, point: Vec2) -> f32 {
+    bounds.clamp_point(point).distance_squared(point)
+}
+
+fn ordered(a: Handle, b: Handle) -> (Handle, Handle) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the operations both indices share, so every test runs against each of them
+    trait Index {
+        fn insert(&mut self, bounds: Rect, value: usize) -> Handle;
+        fn remove(&mut self, handle: Handle) -> Option<usize>;
+        fn set_bounds(&mut self, handle: Handle, bounds: Rect) -> bool;
+        fn get(&self, handle: Handle) -> Option<usize>;
+        fn query_rect(&self, rect: Rect) -> Vec<Handle>;
+        fn query_radius(&self, center: Vec2, radius: f32) -> Vec<Handle>;
+        fn nearest(&self, point: Vec2) -> Option<Handle>;
+        fn pairs(&self) -> Vec<(Handle, Handle)>;
+    }
+
+    macro_rules! impl_index {
+        ($name:ident) => {
+            impl Index for $name<usize> {
+                fn insert(&mut self, bounds: Rect, value: usize) -> Handle {
+                    $name::insert(self, bounds, value)
+                }
+                fn remove(&mut self, handle: Handle) -> Option<usize> {
+                    $name::remove(self, handle)
+                }
+                fn set_bounds(&mut self, handle: Handle, bounds: Rect) -> bool {
+                    $name::set_bounds(self, handle, bounds)
+                }
+                fn get(&self, handle: Handle) -> Option<usize> {
+                    $name::get(self, handle).copied()
+                }
+                fn query_rect(&self, rect: Rect) -> Vec<Handle> {
+                    $name::query_rect(self, rect)
+                }
+                fn query_radius(&self, center: Vec2, radius: f32) -> Vec<Handle> {
+                    $name::query_radius(self, center, radius)
+                }
+                fn nearest(&self, point: Vec2) -> Option<Handle> {
+                    $name::nearest(self, point)
+                }
+                fn pairs(&self) -> Vec<(Handle, Handle)> {
+                    $name::pairs(self)
+                }
+            }
+        };
+    }
+
+    impl_index!(SpatialHash);
+    impl_index!(QuadTree);
+
+    fn indices() -> Vec<Box<dyn Index>> {
+        let world = Rect::new(Vec2::ZERO, Vec2::splat(100.0));
+        vec![Box::new(SpatialHash::<usize>::new(7.0)), Box::new(QuadTree::<usize>::with_limits(world, 4, 6))]
+    }
+
+    // xorshift, so failures reproduce
+    struct Random(u32);
+
+    impl Random {
+        fn next(&mut self, range: f32) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            (self.0 % 10_000) as f32 / 10_000.0 * range
+        }
+
+        fn rect(&mut self, max_size: f32) -> Rect {
+            let min = Vec2::new(self.next(110.0) - 5.0, self.next(110.0) - 5.0);
+            Rect::from_min_size(min, Vec2::new(self.next(max_size), self.next(max_size)))
+        }
+    }
+
+    fn values(index: &dyn Index, handles: Vec<Handle>) -> Vec<usize> {
+        let mut values: Vec<usize> = handles.into_iter().map(|handle| index.get(handle).unwrap()).collect();
+        values.sort_unstable();
+        values
+    }
+
+    #[test]
+    fn stale_handles_are_rejected() {
+        for mut index in indices() {
+            let a = index.insert(Rect::new(Vec2::ZERO, Vec2::ONE), 1);
+            assert_eq!(index.remove(a), Some(1));
+
+            // the new item reuses the slot, but not the handle
+            let b = index.insert(Rect::new(Vec2::ZERO, Vec2::ONE), 2);
+            assert_ne!(a, b);
+            assert_eq!(index.get(a), None);
+            assert!(!index.set_bounds(a, Rect::new(Vec2::splat(5.0), Vec2::splat(6.0))));
+            assert_eq!(index.remove(a), None);
+
+            assert_eq!(index.get(b), Some(2));
+            assert_eq!(index.query_rect(Rect::new(Vec2::ZERO, Vec2::ONE)), vec![b]);
+        }
+    }
+
+    #[test]
+    fn queries_match_brute_force_after_moves() {
+        for mut index in indices() {
+            let mut random = Random(12345);
+            let mut items: Vec<(Handle, Rect, bool)> = (0..300)
+                .map(|value| {
+                    let bounds = random.rect(10.0);
+                    (index.insert(bounds, value), bounds, true)
+                })
+                .collect();
+
+            for (i, item) in items.iter_mut().enumerate().step_by(3) {
+                if i % 2 == 0 {
+                    assert!(index.remove(item.0).is_some());
+                    item.2 = false;
+                } else {
+                    item.1 = random.rect(5.0);
+                    assert!(index.set_bounds(item.0, item.1));
+                }
+            }
+
+            let live: Vec<(usize, Rect)> =
+                items.iter().enumerate().filter(|(_, item)| item.2).map(|(value, item)| (value, item.1)).collect();
+            let brute_force = |keep: &dyn Fn(Rect) -> bool| -> Vec<usize> {
+                live.iter().filter(|(_, bounds)| keep(*bounds)).map(|(value, _)| *value).collect()
+            };
+
+            for _ in 0..50 {
+                let rect = random.rect(25.0);
+                assert_eq!(values(&*index, index.query_rect(rect)), brute_force(&|bounds| bounds.intersects(rect)));
+
+                let center = Vec2::new(random.next(140.0) - 20.0, random.next(140.0) - 20.0);
+                let radius = random.next(20.0);
+                assert_eq!(
+                    values(&*index, index.query_radius(center, radius)),
+                    brute_force(&|bounds| distance_squared(bounds, center) <= radius * radius),
+                );
+
+                let nearest = index.nearest(center).unwrap();
+                let closest = live.iter().map(|(_, bounds)| distance_squared(*bounds, center)).fold(f32::MAX, f32::min);
+                assert_eq!(distance_squared(items[index.get(nearest).unwrap()].1, center), closest);
+            }
+
+            let mut pairs: Vec<(usize, usize)> = index
+                .pairs()
+                .into_iter()
+                .map(|(a, b)| {
+                    let (a, b) = (index.get(a).unwrap(), index.get(b).unwrap());
+                    (a.min(b), a.max(b))
+                })
+                .collect();
+            pairs.sort_unstable();
+
+            let mut expected = Vec::new();
+            for (i, (a, bounds_a)) in live.iter().enumerate() {
+                for (b, bounds_b) in &live[i + 1..] {
+                    if bounds_a.intersects(*bounds_b) {
+                        expected.push((*a, *b));
+                    }
+                }
+            }
+            assert_eq!(pairs, expected);
+        }
+    }
+
+    #[test]
+    fn nearest_far_outside_the_items() {
+        let mut hash = SpatialHash::new(1.0);
+        let origin = hash.insert(Rect::new(Vec2::ZERO, Vec2::splat(0.5)), 0);
+
+        // a million empty rings lie between these and the item, which are skipped rather than walked
+        assert_eq!(hash.nearest(Vec2::new(1.0e6, -1.0e6)), Some(origin));
+        assert_eq!(hash.nearest(Vec2::new(f32::MAX, f32::MIN)), Some(origin));
+        assert_eq!(hash.nearest(Vec2::splat(f32::MIN)), Some(origin));
+
+        let mut random = Random(777);
+        let items: Vec<Rect> = (0..100).map(|_| random.rect(3.0)).collect();
+        let mut hash = SpatialHash::new(2.0);
+        for (value, bounds) in items.iter().enumerate() {
+            hash.insert(*bounds, value);
+        }
+
+        for point in [Vec2::new(-5000.0, 40.0), Vec2::new(30.0, 9000.0), Vec2::splat(-3.0e4), Vec2::new(2.0e5, 1.0e5)] {
+            let nearest = hash.nearest(point).unwrap();
+            let closest = items.iter().map(|bounds| distance_squared(*bounds, point)).fold(f32::MAX, f32::min);
+            assert_eq!(distance_squared(items[*hash.get(nearest).unwrap()], point), closest);
+        }
+    }
+}
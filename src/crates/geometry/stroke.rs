@@ -0,0 +1,351 @@
+use std::f32::consts::PI;
+
+use super::colour::Colour;
+use super::shapes::Segments;
+use super::vector::{Vec2, EPSILON};
+use super::Vertex;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest a miter may be relative to half the width before it falls back to a bevel.
+    pub miter_limit: f32,
+    /// Alternating lengths of dashes and gaps, empty for a solid line. Odd length patterns are repeated, see `dash`,
+    /// and patterns `is_valid_dash_pattern` rejects draw a solid line as well.
+    pub dash_pattern: Vec<f32>,
+    pub dash_offset: f32,
+    pub segments: Segments,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> StrokeStyle {
+        StrokeStyle {
+            width: 1.0,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+            dash_pattern: Vec::new(),
+            dash_offset: 0.0,
+            segments: Segments::default(),
+        }
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> StrokeStyle {
+        StrokeStyle { width, ..Default::default() }
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> StrokeStyle {
+        self.join = join;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> StrokeStyle {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> StrokeStyle {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    /// Dashes starting `offset` into the pattern, or a solid line if `is_valid_dash_pattern` rejects it.
+    pub fn with_dashes(mut self, pattern: Vec<f32>, offset: f32) -> StrokeStyle {
+        self.dash_pattern = pattern;
+        self.dash_offset = offset;
+        self
+    }
+
+    pub fn with_segments(mut self, segments: Segments) -> StrokeStyle {
+        self.segments = segments;
+        self
+    }
+}
+
+/// Turns a polyline, or a polygon when `closed` is set, into triangles covering its outline.
+///
+/// Joins and caps overlap the segments they connect, so strokes are meant to be drawn with a single opaque colour.
+pub fn stroke(points: &[Vec2], closed: bool, style: &StrokeStyle, colour: Colour) -> (Vec<Vertex>, Vec<u32>) {
+    let mut builder = StrokeBuilder { vertices: Vec::new(), indices: Vec::new(), colour, style };
+
+    // like SVG, an empty or invalid pattern draws a solid line
+    match dash(points, closed, &style.dash_pattern, style.dash_offset) {
+        Some(dashes) => {
+            for dash in dashes {
+                builder.polyline(&dash, false);
+            }
+        }
+        None => builder.polyline(points, closed),
+    }
+
+    (builder.vertices, builder.indices)
+}
+
+/// Whether `dash` accepts the pattern: not empty, no negative or infinite lengths, and not all zero.
+pub fn is_valid_dash_pattern(pattern: &[f32]) -> bool {
+    pattern.iter().all(|length| length.is_finite() && *length >= 0.0) && pattern.iter().sum::<f32>() > 0.0
+}
+
+/// Splits a polyline into the open pieces left visible by a dash pattern of alternating on and off lengths.
+///
+/// Like SVG's `stroke-dasharray`, a pattern with an odd number of lengths is repeated once, so `[5.0]` means five on
+/// and five off. Returns `None` for a pattern `is_valid_dash_pattern` rejects.
+pub fn dash(points: &[Vec2], closed: bool, pattern: &[f32], offset: f32) -> Option<Vec<Vec<Vec2>>> {
+    if !is_valid_dash_pattern(pattern) {
+        return None;
+    }
+    if points.len() < 2 {
+        return Some(vec![points.to_vec()]);
+    }
+
+    let pattern = if pattern.len() % 2 == 1 { pattern.repeat(2) } else { pattern.to_vec() };
+    let total: f32 = pattern.iter().sum();
+
+    let mut path = points.to_vec();
+    if closed {
+        path.push(points[0]);
+    }
+
+    // find where in the pattern the offset starts
+    let mut index = 0;
+    let mut on = true;
+    let mut remaining = pattern[0];
+    let mut skip = offset.rem_euclid(total);
+    while skip > 0.0 {
+        if skip < remaining {
+            remaining -= skip;
+            break;
+        }
+        skip -= remaining;
+        index = (index + 1) % pattern.len();
+        on = !on;
+        remaining = pattern[index];
+    }
+
+    let mut dashes = Vec::new();
+    let mut current = if on { vec![path[0]] } else { Vec::new() };
+
+    for segment in path.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let length = start.distance(end);
+        let mut travelled = 0.0;
+
+        while length - travelled > remaining {
+            travelled += remaining;
+            let point = start.lerp(end, travelled / length);
+
+            current.push(point);
+            if on {
+                dashes.push(std::mem::take(&mut current));
+            }
+
+            index = (index + 1) % pattern.len();
+            on = !on;
+            remaining = pattern[index];
+        }
+
+        remaining -= length - travelled;
+        if on {
+            current.push(end);
+        }
+    }
+
+    if on && current.len() > 1 {
+        dashes.push(current);
+    }
+
+    dashes.retain(|dash| dash.len() > 1);
+    Some(dashes)
+}
+
+struct StrokeBuilder<'a> {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    colour: Colour,
+    style: &'a StrokeStyle,
+}
+
+impl StrokeBuilder<'_> {
+    fn polyline(&mut self, points: &[Vec2], closed: bool) {
+        let mut points = points.to_vec();
+        points.dedup_by(|a, b| a.distance_squared(*b) <= EPSILON * EPSILON);
+        if closed && points.len() > 2 && points[0].distance_squared(points[points.len() - 1]) <= EPSILON * EPSILON {
+            points.pop();
+        }
+        if points.len() < 2 {
+            return;
+        }
+
+        let half = self.style.width.abs() * 0.5;
+        let count = points.len();
+        let segment_count = if closed { count } else { count - 1 };
+
+        for i in 0..segment_count {
+            let (start, end) = (points[i], points[(i + 1) % count]);
+            let normal = (end - start).normalize().perpendicular() * half;
+            self.quad(start + normal, end + normal, end - normal, start - normal);
+        }
+
+        let joins = if closed { 0..count } else { 1..count - 1 };
+        for i in joins {
+            let previous = points[(i + count - 1) % count];
+            let next = points[(i + 1) % count];
+            self.join(previous, points[i], next, half);
+        }
+
+        if !closed {
+            self.cap(points[0], (points[0] - points[1]).normalize(), half);
+            self.cap(points[count - 1], (points[count - 1] - points[count - 2]).normalize(), half);
+        }
+    }
+
+    fn join(&mut self, previous: Vec2, point: Vec2, next: Vec2, half: f32) {
+        let incoming = (point - previous).normalize();
+        let outgoing = (next - point).normalize();
+        let turn = incoming.cross(outgoing);
+        if turn.abs() <= EPSILON && incoming.dot(outgoing) > 0.0 {
+            return;
+        }
+
+        // the gap to fill is on the outside of the turn
+        let side = if turn > 0.0 { -1.0 } else { 1.0 };
+        let from = incoming.perpendicular() * half * side;
+        let to = outgoing.perpendicular() * half * side;
+
+        match self.style.join {
+            LineJoin::Round => self.round(point, from, to, half, side),
+            LineJoin::Miter => {
+                let bisector = (from + to).normalize();
+                let cosine = bisector.dot(from) / half;
+                if bisector == Vec2::ZERO || cosine <= EPSILON || 1.0 / cosine > self.style.miter_limit {
+                    self.triangle(point, point + from, point + to);
+                } else {
+                    let tip = point + bisector * (half / cosine);
+                    self.triangle(point, point + from, tip);
+                    self.triangle(point, tip, point + to);
+                }
+            }
+            LineJoin::Bevel => self.triangle(point, point + from, point + to),
+        }
+    }
+
+    // `direction` points away from the line
+    fn cap(&mut self, point: Vec2, direction: Vec2, half: f32) {
+        let normal = direction.perpendicular() * half;
+
+        match self.style.cap {
+            LineCap::Butt => {}
+            LineCap::Square => {
+                let extension = direction * half;
+                self.quad(point + normal, point + normal + extension, point - normal + extension, point - normal);
+            }
+            LineCap::Round => self.round(point, -normal, normal, half, -1.0),
+        }
+    }
+
+    // fan around `center` from `from` to `to`, turning clockwise for a positive `side`
+    fn round(&mut self, center: Vec2, from: Vec2, to: Vec2, radius: f32, side: f32) {
+        let mut sweep = to.angle() - from.angle();
+        if side > 0.0 {
+            if sweep > 0.0 { sweep -= 2.0 * PI }
+        } else if sweep < 0.0 {
+            sweep += 2.0 * PI
+        }
+
+        let count = self.style.segments.for_arc(radius, sweep);
+        let mut last = center + from;
+        for i in 1..=count {
+            let next = center + from.rotate(sweep * i as f32 / count as f32);
+            self.triangle(center, last, next);
+            last = next;
+        }
+    }
+
+    fn vertex(&mut self, position: Vec2) -> u32 {
+        self.vertices.push(Vertex::new(position, self.colour));
+        self.vertices.len() as u32 - 1
+    }
+
+    fn triangle(&mut self, a: Vec2, b: Vec2, c: Vec2) {
+        let indices = [self.vertex(a), self.vertex(b), self.vertex(c)];
+        self.indices.extend_from_slice(&indices);
+    }
+
+    fn quad(&mut self, a: Vec2, b: Vec2, c: Vec2, d: Vec2) {
+        let [a, b, c, d] = [self.vertex(a), self.vertex(b), self.vertex(c), self.vertex(d)];
+        self.indices.extend_from_slice(&[a, b, c, c, d, a]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lengths(dashes: &[Vec<Vec2>]) -> Vec<f32> {
+        dashes.iter().map(|dash| dash.windows(2).map(|pair| pair[0].distance(pair[1])).sum()).collect()
+    }
+
+    #[test]
+    fn single_length_pattern_alternates() {
+        let line = [Vec2::new(0.0, 0.0), Vec2::new(20.0, 0.0)];
+        let dashes = dash(&line, false, &[5.0], 0.0).unwrap();
+        assert_eq!(dashes.len(), 2);
+        assert_eq!(dashes[1][0], Vec2::new(10.0, 0.0));
+        assert_eq!(lengths(&dashes), vec![5.0, 5.0]);
+    }
+
+    #[test]
+    fn odd_pattern_repeats_twice() {
+        // 5 on, 3 off, 2 on, 5 off, 3 on, 2 off
+        let line = [Vec2::new(0.0, 0.0), Vec2::new(20.0, 0.0)];
+        let dashes = dash(&line, false, &[5.0, 3.0, 2.0], 0.0).unwrap();
+        let starts: Vec<f32> = dashes.iter().map(|dash| dash[0].x).collect();
+        assert_eq!(starts, vec![0.0, 8.0, 15.0]);
+        assert_eq!(lengths(&dashes), vec![5.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn offset_into_odd_pattern() {
+        let line = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0)];
+        // 7 into [5, 3, 2, 5, 3, 2] lands in the first gap
+        let dashes = dash(&line, false, &[5.0, 3.0, 2.0], 7.0).unwrap();
+        assert_eq!(dashes[0][0], Vec2::new(1.0, 0.0));
+        assert_eq!(lengths(&dashes)[0], 2.0);
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let line = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0)];
+        assert!(dash(&line, false, &[], 0.0).is_none());
+        assert!(dash(&line, false, &[0.0, 0.0], 0.0).is_none());
+        assert!(dash(&line, false, &[2.0, -1.0], 0.0).is_none());
+        assert!(dash(&line, false, &[f32::INFINITY, 1.0], 0.0).is_none());
+
+        // strokes with invalid patterns are drawn solid instead
+        let solid = stroke(&line, false, &StrokeStyle::new(1.0), Colour::WHITE);
+        for pattern in [vec![], vec![0.0], vec![2.0, -1.0], vec![f32::NAN]] {
+            assert_eq!(stroke(&line, false, &StrokeStyle::new(1.0).with_dashes(pattern.clone(), 0.0), Colour::WHITE), solid);
+
+            let literal = StrokeStyle { dash_pattern: pattern, ..StrokeStyle::new(1.0) };
+            assert_eq!(stroke(&line, false, &literal, Colour::WHITE), solid);
+        }
+    }
+}
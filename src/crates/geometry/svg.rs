@@ -0,0 +1,675 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use roxmltree::{Document, Node};
+
+use super::colour::Colour;
+use super::matrix::Mat3;
+use super::path::{append, Path};
+use super::rect::Rect;
+use super::stroke::StrokeStyle;
+use super::vector::{Vec2, Vec3};
+use super::Vertex;
+
+/// Filled and stroked shapes of an SVG document, flattened into one coloured mesh.
+///
+/// SVG's y axis points down, so positions are flipped to keep y pointing up, with the view box flipped to match.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SvgMesh {
+    pub view_box: Option<Rect>,
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+#[derive(Debug)]
+pub enum SvgError {
+    Io(io::Error),
+    Xml(roxmltree::Error),
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SvgError::Io(error) => write!(f, "failed to read svg: {}", error),
+            SvgError::Xml(error) => write!(f, "failed to parse svg: {}", error),
+        }
+    }
+}
+
+impl Error for SvgError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SvgError::Io(error) => Some(error),
+            SvgError::Xml(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for SvgError {
+    fn from(error: io::Error) -> SvgError {
+        SvgError::Io(error)
+    }
+}
+
+impl From<roxmltree::Error> for SvgError {
+    fn from(error: roxmltree::Error) -> SvgError {
+        SvgError::Xml(error)
+    }
+}
+
+pub fn load_svg<P: AsRef<std::path::Path>>(file: P, tolerance: f32) -> Result<SvgMesh, SvgError> {
+    parse_svg(&fs::read_to_string(file)?, tolerance)
+}
+
+/// Supports `path`, `rect`, `circle`, `ellipse`, `line`, `polyline` and `polygon` elements inside any number of
+/// groups, with solid fill and stroke colours and transforms. Fills use the even-odd rule. Gradients and other paint
+/// servers draw nothing unless they name a fallback colour, and text and images are skipped.
+pub fn parse_svg(text: &str, tolerance: f32) -> Result<SvgMesh, SvgError> {
+    let document = Document::parse(text)?;
+    let root = document.root_element();
+
+    let flip = Mat3::from_scale(Vec2::new(1.0, -1.0));
+    let view_box = root.attribute("viewBox").and_then(|view_box| {
+        match numbers(view_box)[..] {
+            [x, y, width, height] => Some(Rect::new(Vec2::new(x, -y), Vec2::new(x + width, -y - height))),
+            _ => None,
+        }
+    });
+
+    let style = Style { fill: Some(Colour::BLACK), stroke: None, stroke_width: 1.0, colour: Colour::BLACK, transform: flip };
+    let mut mesh = (Vec::new(), Vec::new());
+    visit(root, &style, tolerance, &mut mesh);
+
+    Ok(SvgMesh { view_box, vertices: mesh.0, indices: mesh.1 })
+}
+
+// the inherited presentation attributes
+#[derive(Clone)]
+struct Style {
+    fill: Option<Colour>,
+    stroke: Option<Colour>,
+    stroke_width: f32,
+    // the `color` property, used by `currentColor`
+    colour: Colour,
+    transform: Mat3,
+}
+
+fn visit(node: Node, parent: &Style, tolerance: f32, mesh: &mut (Vec<Vertex>, Vec<u32>)) {
+    let name = node.tag_name().name();
+    if matches!(name, "defs" | "clipPath" | "mask" | "symbol" | "pattern" | "marker" | "linearGradient" | "radialGradient" | "style" | "text") {
+        return;
+    }
+
+    let style = style(node, parent);
+    if property(node, "display") == Some("none") {
+        return;
+    }
+
+    if let Some(path) = shape(node) {
+        let path = path.transform(style.transform);
+        if let Some(fill) = style.fill {
+            append(mesh, path.fill(tolerance, fill));
+        }
+        if let Some(stroke) = style.stroke {
+            // strokes are widened by the average scale of the transform
+            let width = style.stroke_width * style.transform.determinant().abs().sqrt();
+            append(mesh, path.stroke(tolerance, &StrokeStyle::new(width), stroke));
+        }
+    }
+
+    for child in node.children().filter(|child| child.is_element()) {
+        visit(child, &style, tolerance, mesh);
+    }
+}
+
+fn style(node: Node, parent: &Style) -> Style {
+    let mut style = parent.clone();
+
+    if let Some(colour) = property(node, "color").and_then(parse_colour) {
+        style.colour = colour;
+    }
+    if let Some(fill) = property(node, "fill").and_then(|fill| paint(fill, style.colour)) {
+        style.fill = fill;
+    }
+    if let Some(stroke) = property(node, "stroke").and_then(|stroke| paint(stroke, style.colour)) {
+        style.stroke = stroke;
+    }
+    if let Some(width) = property(node, "stroke-width").and_then(|width| numbers(width).first().copied()) {
+        style.stroke_width = width;
+    }
+    if let Some(transform) = node.attribute("transform") {
+        style.transform *= parse_transform(transform);
+    }
+
+    style
+}
+
+// declarations in the `style` attribute take priority over presentation attributes
+fn property<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    let declared = node.attribute("style").and_then(|style| {
+        style.split(';').rev().find_map(|declaration| {
+            let (key, value) = declaration.split_once(':')?;
+            if key.trim() == name { Some(value.trim()) } else { None }
+        })
+    });
+
+    declared.or_else(|| node.attribute(name).map(str::trim))
+}
+
+fn shape(node: Node) -> Option<Path> {
+    let length = |name: &str| node.attribute(name).and_then(|value| numbers(value).first().copied());
+
+    let mut path = Path::new();
+    match node.tag_name().name() {
+        "path" => path = parse_path_data(node.attribute("d")?),
+        "rect" => {
+            let (x, y) = (length("x").unwrap_or(0.0), length("y").unwrap_or(0.0));
+            let (width, height) = (length("width")?, length("height")?);
+            if width <= 0.0 || height <= 0.0 {
+                return None;
+            }
+
+            // a missing corner radius copies the other one
+            let rx = length("rx").or_else(|| length("ry")).unwrap_or(0.0).clamp(0.0, width * 0.5);
+            let ry = length("ry").or_else(|| length("rx")).unwrap_or(0.0).clamp(0.0, height * 0.5);
+            let radii = Vec2::new(rx, ry);
+
+            path.move_to(Vec2::new(x + rx, y))
+                .line_to(Vec2::new(x + width - rx, y))
+                .arc_to(radii, 0.0, false, true, Vec2::new(x + width, y + ry))
+                .line_to(Vec2::new(x + width, y + height - ry))
+                .arc_to(radii, 0.0, false, true, Vec2::new(x + width - rx, y + height))
+                .line_to(Vec2::new(x + rx, y + height))
+                .arc_to(radii, 0.0, false, true, Vec2::new(x, y + height - ry))
+                .line_to(Vec2::new(x, y + ry))
+                .arc_to(radii, 0.0, false, true, Vec2::new(x + rx, y))
+                .close();
+        }
+        "circle" | "ellipse" => {
+            let center = Vec2::new(length("cx").unwrap_or(0.0), length("cy").unwrap_or(0.0));
+            let radii = match length("r") {
+                Some(radius) => Vec2::splat(radius),
+                None => Vec2::new(length("rx")?, length("ry")?),
+            };
+            if radii.x <= 0.0 || radii.y <= 0.0 {
+                return None;
+            }
+
+            path.move_to(center + Vec2::new(radii.x, 0.0))
+                .arc_to(radii, 0.0, false, true, center - Vec2::new(radii.x, 0.0))
+                .arc_to(radii, 0.0, false, true, center + Vec2::new(radii.x, 0.0))
+                .close();
+        }
+        "line" => {
+            path.move_to(Vec2::new(length("x1").unwrap_or(0.0), length("y1").unwrap_or(0.0)))
+                .line_to(Vec2::new(length("x2").unwrap_or(0.0), length("y2").unwrap_or(0.0)));
+        }
+        "polyline" | "polygon" => {
+            let points: Vec<Vec2> = numbers(node.attribute("points")?)
+                .chunks_exact(2)
+                .map(|pair| Vec2::new(pair[0], pair[1]))
+                .collect();
+            path = Path::from_points(&points, node.tag_name().name() == "polygon");
+        }
+        _ => return None,
+    }
+
+    Some(path)
+}
+
+/// `None` for `inherit`, which keeps the parent's paint, and `Some(None)` for `none` and anything else that is not a
+/// solid colour.
+fn paint(value: &str, current_colour: Colour) -> Option<Option<Colour>> {
+    let value = value.trim();
+
+    // gradients and patterns are not supported, but may be followed by a colour to use instead
+    if let Some(reference) = value.strip_prefix("url(") {
+        return match reference.split_once(')') {
+            Some((_, fallback)) if !fallback.trim().is_empty() => paint(fallback, current_colour),
+            _ => Some(None),
+        };
+    }
+
+    match value {
+        "inherit" => None,
+        "currentColor" => Some(Some(current_colour)),
+        "none" | "transparent" => Some(None),
+        _ => Some(parse_colour(value)),
+    }
+}
+
+/// Parses hex colours, `rgb()`, `rgba()` and the basic colour keywords.
+pub fn parse_colour(value: &str) -> Option<Colour> {
+    let value = value.trim();
+
+    if value.starts_with('#') {
+        return Colour::from_hex(value);
+    }
+
+    let arguments = value
+        .strip_prefix("rgb(")
+        .or_else(|| value.strip_prefix("rgba("))
+        .and_then(|rest| rest.strip_suffix(')'));
+    if let Some(arguments) = arguments {
+        // colour channels are 0-255 or percentages, alpha is 0-1 or a percentage
+        let channels: Vec<f32> = arguments
+            .split(',')
+            .enumerate()
+            .map(|(i, channel)| {
+                let channel = channel.trim();
+                match channel.strip_suffix('%') {
+                    Some(percent) => percent.trim().parse::<f32>().ok().map(|percent| percent / 100.0),
+                    None if i < 3 => channel.parse::<f32>().ok().map(|channel| channel / 255.0),
+                    None => channel.parse::<f32>().ok(),
+                }
+            })
+            .collect::<Option<_>>()?;
+
+        return match channels[..] {
+            [r, g, b] => Some(Colour::rgb(r, g, b).clamp()),
+            [r, g, b, a] => Some(Colour::rgba(r, g, b, a).clamp()),
+            _ => None,
+        };
+    }
+
+    Colour::from_name(value)
+}
+
+/// Parses an SVG transform list such as `translate(10 20) rotate(45)`.
+pub fn parse_transform(value: &str) -> Mat3 {
+    let mut matrix = Mat3::IDENTITY;
+
+    for item in value.split_inclusive(')') {
+        let Some((name, arguments)) = item.split_once('(') else { break };
+        let arguments = numbers(arguments.trim_end_matches(')'));
+        let name = name.trim_matches(|c: char| c.is_whitespace() || c == ',');
+
+        let transform = match (name, &arguments[..]) {
+            ("matrix", [a, b, c, d, e, f]) => {
+                Mat3::from_cols(Vec3::new(*a, *b, 0.0), Vec3::new(*c, *d, 0.0), Vec3::new(*e, *f, 1.0))
+            }
+            ("translate", [x]) => Mat3::from_translation(Vec2::new(*x, 0.0)),
+            ("translate", [x, y]) => Mat3::from_translation(Vec2::new(*x, *y)),
+            ("scale", [scale]) => Mat3::from_scale(Vec2::splat(*scale)),
+            ("scale", [x, y]) => Mat3::from_scale(Vec2::new(*x, *y)),
+            ("rotate", [angle]) => Mat3::from_rotation(angle.to_radians()),
+            ("rotate", [angle, x, y]) => {
+                let center = Vec2::new(*x, *y);
+                Mat3::from_translation(center) * Mat3::from_rotation(angle.to_radians()) * Mat3::from_translation(-center)
+            }
+            ("skewX", [angle]) => Mat3::from_cols(Vec3::X, Vec3::new(angle.to_radians().tan(), 1.0, 0.0), Vec3::Z),
+            ("skewY", [angle]) => Mat3::from_cols(Vec3::new(1.0, angle.to_radians().tan(), 0.0), Vec3::Y, Vec3::Z),
+            _ => break,
+        };
+
+        matrix *= transform;
+    }
+
+    matrix
+}
+
+/// Parses SVG path data, keeping everything before the first error as the SVG specification asks.
+pub fn parse_path_data(data: &str) -> Path {
+    let mut parser = Parser { data: data.as_bytes(), position: 0 };
+    let mut path = Path::new();
+    let mut command = None;
+    let mut previous_control = None;
+
+    loop {
+        parser.skip_separators();
+        if parser.position >= parser.data.len() {
+            break;
+        }
+
+        if let Some(next) = parser.command() {
+            command = Some(next);
+        } else if matches!(command, None | Some(b'Z') | Some(b'z')) {
+            break;
+        }
+
+        let Some(current) = command else { break };
+        match segment(&mut parser, &mut path, current, previous_control) {
+            Some(control) => previous_control = control,
+            None => break,
+        }
+
+        // coordinates following a move are implicit lines
+        command = match current {
+            b'M' => Some(b'L'),
+            b'm' => Some(b'l'),
+            other => Some(other),
+        };
+    }
+
+    path
+}
+
+// reads the arguments of one command, returning the control point used for smooth curves
+fn segment(parser: &mut Parser, path: &mut Path, command: u8, previous_control: Option<(u8, Vec2)>) -> Option<Option<(u8, Vec2)>> {
+    let current = path.current_point();
+    let origin = if command.is_ascii_lowercase() { current } else { Vec2::ZERO };
+    let kind = command.to_ascii_uppercase();
+
+    // smooth curves mirror the previous control point if the previous command was the same kind of curve
+    let reflect = |kinds: [u8; 2]| match previous_control {
+        Some((previous, control)) if kinds.contains(&previous) => current * 2.0 - control,
+        _ => current,
+    };
+
+    match kind {
+        b'M' => {
+            path.move_to(origin + parser.point()?);
+        }
+        b'L' => {
+            path.line_to(origin + parser.point()?);
+        }
+        b'H' => {
+            let x = parser.number()? + origin.x;
+            path.line_to(Vec2::new(x, current.y));
+        }
+        b'V' => {
+            let y = parser.number()? + origin.y;
+            path.line_to(Vec2::new(current.x, y));
+        }
+        b'C' => {
+            let (control1, control2, to) = (origin + parser.point()?, origin + parser.point()?, origin + parser.point()?);
+            path.cubic_to(control1, control2, to);
+            return Some(Some((b'C', control2)));
+        }
+        b'S' => {
+            let (control2, to) = (origin + parser.point()?, origin + parser.point()?);
+            path.cubic_to(reflect([b'C', b'S']), control2, to);
+            return Some(Some((b'S', control2)));
+        }
+        b'Q' => {
+            let (control, to) = (origin + parser.point()?, origin + parser.point()?);
+            path.quad_to(control, to);
+            return Some(Some((b'Q', control)));
+        }
+        b'T' => {
+            let control = reflect([b'Q', b'T']);
+            path.quad_to(control, origin + parser.point()?);
+            return Some(Some((b'T', control)));
+        }
+        b'A' => {
+            let radii = Vec2::new(parser.number()?, parser.number()?);
+            let rotation = parser.number()?.to_radians();
+            let (large_arc, sweep) = (parser.flag()?, parser.flag()?);
+            path.arc_to(radii, rotation, large_arc, sweep, origin + parser.point()?);
+        }
+        b'Z' => {
+            path.close();
+        }
+        _ => return None,
+    }
+
+    Some(None)
+}
+
+fn numbers(value: &str) -> Vec<f32> {
+    let mut parser = Parser { data: value.as_bytes(), position: 0 };
+    let mut numbers = Vec::new();
+    while let Some(number) = parser.number() {
+        numbers.push(number);
+        // skip units such as `px`
+        while parser.position < parser.data.len() && parser.data[parser.position].is_ascii_alphabetic() {
+            parser.position += 1;
+        }
+    }
+    numbers
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.position).copied()
+    }
+
+    fn skip_separators(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r' | b',')) {
+            self.position += 1;
+        }
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let command = self.peek().filter(|c| b"MmLlHhVvCcSsQqTtAaZz".contains(c))?;
+        self.position += 1;
+        Some(command)
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.position;
+
+        if matches!(self.peek(), Some(b'+' | b'-')) {
+            self.position += 1;
+        }
+        self.digits();
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            self.digits();
+        }
+
+        // only treat an `e` as an exponent when digits follow it
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mark = self.position;
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            if self.digits() == 0 {
+                self.position = mark;
+            }
+        }
+
+        let number = std::str::from_utf8(&self.data[start..self.position]).ok()?.parse().ok();
+        if number.is_none() {
+            self.position = start;
+        }
+        number
+    }
+
+    // flags can be written without separators, as in `a1 1 0 00 1 1`
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.peek()? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.position += 1;
+        Some(flag)
+    }
+
+    fn point(&mut self) -> Option<Vec2> {
+        Some(Vec2::new(self.number()?, self.number()?))
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.position += 1;
+        }
+        self.position - start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::path::PathCommand::{Close, CubicTo, LineTo, MoveTo, QuadTo};
+
+    fn v(x: f32, y: f32) -> Vec2 {
+        Vec2::new(x, y)
+    }
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    // colours in the mesh, ignoring how many triangles each one has
+    fn colours(svg: &str) -> Vec<Colour> {
+        let mesh = parse_svg(svg, 0.01).unwrap();
+        let mut colours: Vec<Colour> = Vec::new();
+        for vertex in mesh.vertices {
+            if !colours.contains(&vertex.colour) {
+                colours.push(vertex.colour);
+            }
+        }
+        colours
+    }
+
+    #[test]
+    fn relative_and_implicit_commands() {
+        let path = parse_path_data("M10 10 l5 0 5 5 h-10 v-5z m1 1 2 2 L3 3 4 4");
+        assert_eq!(
+            path.commands(),
+            &[
+                MoveTo(v(10.0, 10.0)),
+                LineTo(v(15.0, 10.0)),
+                LineTo(v(20.0, 15.0)),
+                LineTo(v(10.0, 15.0)),
+                LineTo(v(10.0, 10.0)),
+                Close,
+                // relative to the start of the closed sub path
+                MoveTo(v(11.0, 11.0)),
+                LineTo(v(13.0, 13.0)),
+                LineTo(v(3.0, 3.0)),
+                LineTo(v(4.0, 4.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn compact_numbers_and_errors() {
+        let path = parse_path_data("M0,0L.5.5-1-2e1H1e1V+3");
+        assert_eq!(
+            path.commands(),
+            &[MoveTo(v(0.0, 0.0)), LineTo(v(0.5, 0.5)), LineTo(v(-1.0, -20.0)), LineTo(v(10.0, -20.0)), LineTo(v(10.0, 3.0))]
+        );
+
+        // everything up to the first error is kept
+        let path = parse_path_data("M1 1 L2 2 L3 X 4 4");
+        assert_eq!(path.commands(), &[MoveTo(v(1.0, 1.0)), LineTo(v(2.0, 2.0))]);
+        assert!(parse_path_data("").is_empty());
+    }
+
+    #[test]
+    fn smooth_curves_reflect_their_control_points() {
+        let path = parse_path_data("M0 0 C1 1 2 1 3 0 s2 -1 3 0 M0 0 Q1 1 2 0 t2 0 T6 0");
+        assert_eq!(path.commands()[2], CubicTo { control1: v(4.0, -1.0), control2: v(5.0, -1.0), to: v(6.0, 0.0) });
+        assert_eq!(path.commands()[5], QuadTo { control: v(3.0, -1.0), to: v(4.0, 0.0) });
+        assert_eq!(path.commands()[6], QuadTo { control: v(5.0, 1.0), to: v(6.0, 0.0) });
+
+        // without a curve before it, the first control point is the current point
+        let path = parse_path_data("M1 1 S2 2 3 1");
+        assert_eq!(path.commands()[1], CubicTo { control1: v(1.0, 1.0), control2: v(2.0, 2.0), to: v(3.0, 1.0) });
+    }
+
+    #[test]
+    fn arcs() {
+        // half a circle, with its flags written without separators
+        let path = parse_path_data("M0 0A1 1 0 012 0");
+        assert_close(path.current_point(), v(2.0, 0.0));
+        let bounds = path.bounding_box(0.001).unwrap();
+        assert_close(bounds.min, v(0.0, -1.0));
+        assert_close(bounds.max, v(2.0, 0.0));
+
+        // radii too small to reach are scaled up, and the other sweep goes the other way around
+        let path = parse_path_data("M0 0 a0.5 0.5 0 0 0 2 0");
+        let bounds = path.bounding_box(0.001).unwrap();
+        assert_close(bounds.min, v(0.0, 0.0));
+        assert_close(bounds.max, v(2.0, 1.0));
+
+        // the large arc of a circle through both points goes the long way round
+        let path = parse_path_data("M0 0 A2 2 0 1 1 2 0");
+        assert_close(path.current_point(), v(2.0, 0.0));
+        assert!(path.bounding_box(0.001).unwrap().height() > 3.0);
+
+        // zero radii draw a line
+        assert_eq!(parse_path_data("M0 0 A0 1 0 0 1 2 0").commands()[1], LineTo(v(2.0, 0.0)));
+    }
+
+    #[test]
+    fn transform_lists() {
+        let point = |transform: &str, point: Vec2| parse_transform(transform).transform_point(point);
+
+        assert_close(point("translate(10 20) scale(2)", v(1.0, 1.0)), v(12.0, 22.0));
+        assert_close(point("scale(2),translate(10,20)", v(1.0, 1.0)), v(22.0, 42.0));
+        assert_close(point("translate(5)", v(1.0, 1.0)), v(6.0, 1.0));
+        assert_close(point("scale(2 3)", v(1.0, 1.0)), v(2.0, 3.0));
+        assert_close(point("rotate(90)", v(1.0, 0.0)), v(0.0, 1.0));
+        assert_close(point("rotate(90 1 0)", v(2.0, 0.0)), v(1.0, 1.0));
+        assert_close(point("matrix(1 2 3 4 5 6)", v(1.0, 1.0)), v(9.0, 12.0));
+        assert_close(point("skewX(45)", v(0.0, 1.0)), v(1.0, 1.0));
+        assert_close(point("skewY(45)", v(1.0, 0.0)), v(1.0, 1.0));
+
+        // an invalid transform ends the list
+        assert_close(point("translate(1 2) spin(3) scale(2)", v(0.0, 0.0)), v(1.0, 2.0));
+        assert_close(point("", v(3.0, 4.0)), v(3.0, 4.0));
+    }
+
+    #[test]
+    fn colour_forms() {
+        assert_eq!(parse_colour("#f00"), Some(Colour::RED));
+        assert_eq!(parse_colour("#0000ff"), Some(Colour::BLUE));
+        assert_eq!(parse_colour("#00ff0080"), Some(Colour::from_rgba8(0, 255, 0, 128)));
+        assert_eq!(parse_colour(" Yellow "), Some(Colour::YELLOW));
+        assert_eq!(parse_colour("rgb(255, 0, 255)"), Some(Colour::MAGENTA));
+        assert_eq!(parse_colour("rgb(100%, 100%, 0%)"), Some(Colour::YELLOW));
+        assert_eq!(parse_colour("rgba(0, 0, 255, 0.5)"), Some(Colour::rgba(0.0, 0.0, 1.0, 0.5)));
+        assert_eq!(parse_colour("rgb(300, 0, 0)"), Some(Colour::RED));
+
+        assert_eq!(parse_colour("rgb(1, 2)"), None);
+        assert_eq!(parse_colour("#12"), None);
+        assert_eq!(parse_colour("url(#gradient)"), None);
+        assert_eq!(parse_colour("chartreuse-ish"), None);
+    }
+
+    #[test]
+    fn paints() {
+        assert_eq!(paint("red", Colour::BLACK), Some(Some(Colour::RED)));
+        assert_eq!(paint("none", Colour::BLACK), Some(None));
+        assert_eq!(paint("inherit", Colour::BLACK), None);
+        assert_eq!(paint("currentColor", Colour::BLUE), Some(Some(Colour::BLUE)));
+
+        // paint servers are skipped, unless they name a colour to fall back on
+        assert_eq!(paint("url(#gradient)", Colour::BLACK), Some(None));
+        assert_eq!(paint("url(#gradient) #0f0", Colour::BLACK), Some(Some(Colour::GREEN)));
+        assert_eq!(paint("url(#gradient) none", Colour::BLACK), Some(None));
+        assert_eq!(paint("context-fill", Colour::BLACK), Some(None));
+    }
+
+    #[test]
+    fn fill_inheritance() {
+        let rect = r#"<rect width="1" height="1"/>"#;
+
+        // the root fills black
+        assert_eq!(colours(&format!("<svg>{}</svg>", rect)), vec![Colour::BLACK]);
+        assert_eq!(colours(&format!(r#"<svg><g fill="red"><g>{}</g></g></svg>"#, rect)), vec![Colour::RED]);
+        assert_eq!(colours(&format!(r#"<svg><g fill="red"><g fill="none">{}</g></g></svg>"#, rect)), vec![]);
+        assert_eq!(colours(&format!(r#"<svg><g fill="red"><g fill="inherit">{}</g></g></svg>"#, rect)), vec![Colour::RED]);
+
+        // the style attribute wins over the fill attribute
+        let styled = r#"<svg><rect width="1" height="1" fill="red" style="stroke: none; fill: blue"/></svg>"#;
+        assert_eq!(colours(styled), vec![Colour::BLUE]);
+
+        // gradient fills draw nothing instead of the parent's fill, or their fallback when they have one
+        let gradient = r#"<svg fill="red"><defs><linearGradient id="g"/></defs><rect width="1" height="1" fill="url(#g)"/></svg>"#;
+        assert_eq!(colours(gradient), vec![]);
+        let fallback = r#"<svg fill="red"><rect width="1" height="1" fill="url(#g) yellow"/></svg>"#;
+        assert_eq!(colours(fallback), vec![Colour::YELLOW]);
+
+        // strokes are inherited separately, and currentColor follows the color property
+        let stroked = r#"<svg><g stroke="lime" color="blue"><rect width="1" height="1" fill="currentColor"/></g></svg>"#;
+        assert_eq!(colours(stroked), vec![Colour::BLUE, Colour::from_rgb8(0, 255, 0)]);
+    }
+}
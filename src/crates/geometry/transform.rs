@@ -0,0 +1,80 @@
+use super::matrix::{Mat3, Mat4};
+use super::vector::Vec2;
+
+/// Position, rotation and scale of an object in 2D.
+///
+/// `pivot` is the local point that rotation and scale happen around and that ends up at `position`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform2D {
+    pub position: Vec2,
+    pub rotation: f32,
+    pub scale: Vec2,
+    pub pivot: Vec2,
+}
+
+impl Default for Transform2D {
+    fn default() -> Transform2D {
+        Transform2D::IDENTITY
+    }
+}
+
+impl Transform2D {
+    pub const IDENTITY: Transform2D = Transform2D {
+        position: Vec2::ZERO,
+        rotation: 0.0,
+        scale: Vec2::ONE,
+        pivot: Vec2::ZERO,
+    };
+
+    pub fn from_position(position: Vec2) -> Transform2D {
+        Transform2D { position, ..Transform2D::IDENTITY }
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Transform2D {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec2) -> Transform2D {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_pivot(mut self, pivot: Vec2) -> Transform2D {
+        self.pivot = pivot;
+        self
+    }
+
+    pub fn translate(&mut self, offset: Vec2) {
+        self.position += offset;
+    }
+
+    pub fn rotate(&mut self, angle: f32) {
+        self.rotation += angle;
+    }
+
+    /// Local to world matrix, `translation * rotation * scale * -pivot`.
+    pub fn matrix(self) -> Mat3 {
+        let mut matrix = Mat3::from_scale_rotation_translation(self.scale, self.rotation, self.position);
+        matrix.cols[2] -= matrix.cols[0] * self.pivot.x + matrix.cols[1] * self.pivot.y;
+        matrix
+    }
+
+    /// World to local matrix, `None` when the scale is zero on either axis.
+    pub fn inverse_matrix(self) -> Option<Mat3> {
+        self.matrix().inverse()
+    }
+
+    pub fn to_mat4(self) -> Mat4 {
+        Mat4::from_affine_2d(self.matrix())
+    }
+
+    pub fn transform_point(self, point: Vec2) -> Vec2 {
+        self.matrix().transform_point(point)
+    }
+
+    /// Applies `parent` on top of this transform, for objects attached to another.
+    pub fn then(self, parent: Transform2D) -> Mat3 {
+        parent.matrix() * self.matrix()
+    }
+}
@@ -0,0 +1,190 @@
+use super::colour::Colour;
+use super::polygon;
+use super::vector::Vec2;
+use super::Vertex;
+
+/// Triangulates a simple polygon with optional holes by ear clipping.
+///
+/// The returned indices point into the outline followed by every hole in order, which is the same layout
+/// `triangulate_mesh` uses for its vertices. Either winding is accepted for the outline and the holes. Returns
+/// `None` if the outline has fewer than three points or the polygon intersects itself.
+pub fn triangulate(outline: &[Vec2], holes: &[Vec<Vec2>]) -> Option<Vec<u32>> {
+    if outline.len() < 3 {
+        return None;
+    }
+
+    let mut points = outline.to_vec();
+    let mut ring: Vec<usize> = (0..outline.len()).collect();
+    if polygon::signed_area(outline) < 0.0 {
+        ring.reverse();
+    }
+
+    let mut hole_rings = Vec::with_capacity(holes.len());
+    for hole in holes {
+        let start = points.len();
+        points.extend_from_slice(hole);
+        if hole.len() < 3 {
+            continue;
+        }
+
+        // holes have to wind the opposite way to the outline once they are joined to it
+        let mut hole_ring: Vec<usize> = (start..points.len()).collect();
+        if polygon::signed_area(hole) > 0.0 {
+            hole_ring.reverse();
+        }
+        hole_rings.push(hole_ring);
+    }
+
+    // bridging the rightmost hole first keeps later bridges from crossing earlier ones
+    hole_rings.sort_by(|a, b| max_x(&points, b).total_cmp(&max_x(&points, a)));
+    for hole_ring in hole_rings {
+        ring = bridge_hole(&points, ring, &hole_ring)?;
+    }
+
+    let triangles = clip_ears(&points, ring)?;
+    Some(triangles.into_iter().map(|index| index as u32).collect())
+}
+
+/// Triangulates the polygon into vertices and indices ready for `create_vertex_buffer` and `create_index_buffer`.
+pub fn triangulate_mesh(outline: &[Vec2], holes: &[Vec<Vec2>], colour: Colour) -> Option<(Vec<Vertex>, Vec<u32>)> {
+    let indices = triangulate(outline, holes)?;
+    let vertices = outline
+        .iter()
+        .chain(holes.iter().flatten())
+        .map(|position| Vertex::new(*position, colour))
+        .collect();
+
+    Some((vertices, indices))
+}
+
+fn max_x(points: &[Vec2], ring: &[usize]) -> f32 {
+    ring.iter().map(|index| points[*index].x).fold(f32::MIN, f32::max)
+}
+
+// joins a hole to the outline with a pair of coincident edges, turning both into one ring
+fn bridge_hole(points: &[Vec2], ring: Vec<usize>, hole: &[usize]) -> Option<Vec<usize>> {
+    let (hole_start, hole_point) = hole
+        .iter()
+        .enumerate()
+        .map(|(i, index)| (i, points[*index]))
+        .max_by(|a, b| a.1.x.total_cmp(&b.1.x))?;
+
+    // cast a ray to the right of the hole and find the closest outline edge it hits
+    let mut closest: Option<(f32, usize)> = None;
+    for i in 0..ring.len() {
+        let a = points[ring[i]];
+        let b = points[ring[(i + 1) % ring.len()]];
+        if (a.y > hole_point.y) == (b.y > hole_point.y) {
+            continue;
+        }
+
+        let x = a.x + (hole_point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+        if x < hole_point.x || closest.is_some_and(|(closest_x, _)| x >= closest_x) {
+            continue;
+        }
+
+        let candidate = if a.x > b.x { i } else { (i + 1) % ring.len() };
+        closest = Some((x, candidate));
+    }
+
+    let (hit_x, mut bridge) = closest?;
+    let hit = Vec2::new(hit_x, hole_point.y);
+    let candidate = points[ring[bridge]];
+
+    // a reflex vertex inside the triangle between the hole, the hit and the candidate would block the bridge,
+    // the one closest in angle to the ray is always visible
+    let mut best_angle = f32::MAX;
+    let mut best_distance = f32::MAX;
+    for (i, index) in ring.iter().enumerate() {
+        let point = points[*index];
+        if *index == ring[bridge] || !is_reflex(points, &ring, i) || !contains_point(hole_point, hit, candidate, point) {
+            continue;
+        }
+
+        let offset = point - hole_point;
+        let angle = offset.y.abs().atan2(offset.x);
+        let distance = offset.length_squared();
+        if angle < best_angle || (angle == best_angle && distance < best_distance) {
+            best_angle = angle;
+            best_distance = distance;
+            bridge = i;
+        }
+    }
+
+    let mut merged = Vec::with_capacity(ring.len() + hole.len() + 2);
+    merged.extend_from_slice(&ring[..=bridge]);
+    merged.extend((0..=hole.len()).map(|i| hole[(hole_start + i) % hole.len()]));
+    merged.push(ring[bridge]);
+    merged.extend_from_slice(&ring[bridge + 1..]);
+
+    Some(merged)
+}
+
+fn clip_ears(points: &[Vec2], mut ring: Vec<usize>) -> Option<Vec<usize>> {
+    let mut triangles = Vec::with_capacity(ring.len().saturating_sub(2) * 3);
+    let mut i = 0;
+    let mut attempts = 0;
+
+    while ring.len() > 3 {
+        let length = ring.len();
+        let previous = ring[(i + length - 1) % length];
+        let next = ring[(i + 1) % length];
+
+        if is_ear(points, &ring, previous, ring[i], next) {
+            triangles.extend_from_slice(&[previous, ring[i], next]);
+            ring.remove(i);
+            attempts = 0;
+        } else {
+            i += 1;
+            attempts += 1;
+        }
+
+        // a full lap without an ear means the only way forward is dropping a collinear point
+        if attempts >= ring.len() {
+            let degenerate = (0..ring.len()).find(|j| turn(points, &ring, *j) == 0.0)?;
+            ring.remove(degenerate);
+            attempts = 0;
+        }
+
+        if i >= ring.len() {
+            i = 0;
+        }
+    }
+
+    triangles.extend_from_slice(&ring);
+    Some(triangles)
+}
+
+fn is_ear(points: &[Vec2], ring: &[usize], previous: usize, current: usize, next: usize) -> bool {
+    let (a, b, c) = (points[previous], points[current], points[next]);
+    if (b - a).cross(c - b) <= 0.0 {
+        return false;
+    }
+
+    !ring.iter().any(|index| {
+        let point = points[*index];
+        *index != previous && *index != current && *index != next
+            && point != a && point != b && point != c
+            && contains_point(a, b, c, point)
+    })
+}
+
+fn turn(points: &[Vec2], ring: &[usize], i: usize) -> f32 {
+    let length = ring.len();
+    let previous = points[ring[(i + length - 1) % length]];
+    let current = points[ring[i]];
+    let next = points[ring[(i + 1) % length]];
+    (current - previous).cross(next - current)
+}
+
+fn is_reflex(points: &[Vec2], ring: &[usize], i: usize) -> bool {
+    turn(points, ring, i) <= 0.0
+}
+
+// inclusive of the edges, for triangles of either winding
+fn contains_point(a: Vec2, b: Vec2, c: Vec2, point: Vec2) -> bool {
+    let ab = (b - a).cross(point - a);
+    let bc = (c - b).cross(point - b);
+    let ca = (a - c).cross(point - c);
+    (ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0)
+}
@@ -0,0 +1,293 @@
+use bytemuck::{Pod, Zeroable};
+
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use vulkano::pipeline::graphics::vertex_input::{VertexMember, VertexMemberTy};
+
+pub const EPSILON: f32 = 1e-5;
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+// implements the component-wise operators shared by every vector type
+macro_rules! impl_vector {
+    ($name:ident, $size:expr, $($field:ident),+) => {
+        impl $name {
+            pub const ZERO: $name = $name { $($field: 0.0),+ };
+            pub const ONE: $name = $name { $($field: 1.0),+ };
+
+            pub const fn new($($field: f32),+) -> $name {
+                $name { $($field),+ }
+            }
+
+            pub const fn splat(value: f32) -> $name {
+                $name { $($field: value),+ }
+            }
+
+            pub fn dot(self, other: $name) -> f32 {
+                0.0 $(+ self.$field * other.$field)+
+            }
+
+            pub fn length_squared(self) -> f32 {
+                self.dot(self)
+            }
+
+            pub fn length(self) -> f32 {
+                self.length_squared().sqrt()
+            }
+
+            pub fn distance(self, other: $name) -> f32 {
+                (other - self).length()
+            }
+
+            pub fn distance_squared(self, other: $name) -> f32 {
+                (other - self).length_squared()
+            }
+
+            /// Returns `None` for vectors too short to have a direction.
+            pub fn try_normalize(self) -> Option<$name> {
+                let length = self.length();
+                if length > EPSILON { Some(self / length) } else { None }
+            }
+
+            /// Returns the zero vector for vectors too short to have a direction.
+            pub fn normalize(self) -> $name {
+                self.try_normalize().unwrap_or($name::ZERO)
+            }
+
+            pub fn lerp(self, other: $name, t: f32) -> $name {
+                self + (other - self) * t
+            }
+
+            pub fn min(self, other: $name) -> $name {
+                $name { $($field: self.$field.min(other.$field)),+ }
+            }
+
+            pub fn max(self, other: $name) -> $name {
+                $name { $($field: self.$field.max(other.$field)),+ }
+            }
+
+            pub fn abs(self) -> $name {
+                $name { $($field: self.$field.abs()),+ }
+            }
+
+            pub fn approx_eq(self, other: $name, epsilon: f32) -> bool {
+                true $(&& (self.$field - other.$field).abs() <= epsilon)+
+            }
+
+            pub fn to_array(self) -> [f32; $size] {
+                [$(self.$field),+]
+            }
+        }
+
+        impl From<[f32; $size]> for $name {
+            fn from(array: [f32; $size]) -> $name {
+                let [$($field),+] = array;
+                $name { $($field),+ }
+            }
+        }
+
+        impl From<$name> for [f32; $size] {
+            fn from(vector: $name) -> [f32; $size] {
+                vector.to_array()
+            }
+        }
+
+        impl Index<usize> for $name {
+            type Output = f32;
+
+            fn index(&self, index: usize) -> &f32 {
+                bytemuck::cast_ref::<$name, [f32; $size]>(self).index(index)
+            }
+        }
+
+        impl IndexMut<usize> for $name {
+            fn index_mut(&mut self, index: usize) -> &mut f32 {
+                bytemuck::cast_mut::<$name, [f32; $size]>(self).index_mut(index)
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, other: $name) -> $name {
+                $name { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, other: $name) -> $name {
+                $name { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl Mul for $name {
+            type Output = $name;
+
+            fn mul(self, other: $name) -> $name {
+                $name { $($field: self.$field * other.$field),+ }
+            }
+        }
+
+        impl Div for $name {
+            type Output = $name;
+
+            fn div(self, other: $name) -> $name {
+                $name { $($field: self.$field / other.$field),+ }
+            }
+        }
+
+        impl Mul<f32> for $name {
+            type Output = $name;
+
+            fn mul(self, scalar: f32) -> $name {
+                $name { $($field: self.$field * scalar),+ }
+            }
+        }
+
+        impl Mul<$name> for f32 {
+            type Output = $name;
+
+            fn mul(self, vector: $name) -> $name {
+                vector * self
+            }
+        }
+
+        impl Div<f32> for $name {
+            type Output = $name;
+
+            fn div(self, scalar: f32) -> $name {
+                $name { $($field: self.$field / scalar),+ }
+            }
+        }
+
+        impl Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name { $($field: -self.$field),+ }
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, other: $name) {
+                *self = *self + other;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, other: $name) {
+                *self = *self - other;
+            }
+        }
+
+        impl MulAssign<f32> for $name {
+            fn mul_assign(&mut self, scalar: f32) {
+                *self = *self * scalar;
+            }
+        }
+
+        impl DivAssign<f32> for $name {
+            fn div_assign(&mut self, scalar: f32) {
+                *self = *self / scalar;
+            }
+        }
+
+        // lets the vector types be used directly as fields passed to `impl_vertex!`
+        unsafe impl VertexMember for $name {
+            fn format() -> (VertexMemberTy, usize) {
+                (VertexMemberTy::F32, $size)
+            }
+        }
+    };
+}
+
+impl_vector!(Vec2, 2, x, y);
+impl_vector!(Vec3, 3, x, y, z);
+impl_vector!(Vec4, 4, x, y, z, w);
+
+impl Vec2 {
+    pub const X: Vec2 = Vec2 { x: 1.0, y: 0.0 };
+    pub const Y: Vec2 = Vec2 { x: 0.0, y: 1.0 };
+
+    /// The z component of the 3D cross product, positive when `other` is counter-clockwise of `self`.
+    pub fn cross(self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Rotates the vector by 90 degrees counter-clockwise.
+    pub fn perpendicular(self) -> Vec2 {
+        Vec2 { x: -self.y, y: self.x }
+    }
+
+    pub fn from_angle(angle: f32) -> Vec2 {
+        Vec2 { x: angle.cos(), y: angle.sin() }
+    }
+
+    pub fn angle(self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    pub fn rotate(self, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2 {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        }
+    }
+
+    pub fn extend(self, z: f32) -> Vec3 {
+        Vec3 { x: self.x, y: self.y, z }
+    }
+}
+
+impl Vec3 {
+    pub const X: Vec3 = Vec3 { x: 1.0, y: 0.0, z: 0.0 };
+    pub const Y: Vec3 = Vec3 { x: 0.0, y: 1.0, z: 0.0 };
+    pub const Z: Vec3 = Vec3 { x: 0.0, y: 0.0, z: 1.0 };
+
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn truncate(self) -> Vec2 {
+        Vec2 { x: self.x, y: self.y }
+    }
+
+    pub fn extend(self, w: f32) -> Vec4 {
+        Vec4 { x: self.x, y: self.y, z: self.z, w }
+    }
+}
+
+impl Vec4 {
+    pub fn truncate(self) -> Vec3 {
+        Vec3 { x: self.x, y: self.y, z: self.z }
+    }
+}
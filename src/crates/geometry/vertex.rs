@@ -0,0 +1,66 @@
+use bytemuck::{Pod, Zeroable};
+
+use super::colour::Colour;
+use super::vector::{Vec2, Vec3};
+
+/// 2D vertex with texture coordinates, for images drawn as they are.
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct TexturedVertex {
+    pub position: Vec2,
+    pub uv: Vec2,
+}
+
+/// 2D vertex with texture coordinates and a colour the texture is tinted by.
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct ColouredTexturedVertex {
+    pub position: Vec2,
+    pub colour: Colour,
+    pub uv: Vec2,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct Vertex3D {
+    pub position: Vec3,
+    pub colour: Colour,
+}
+
+/// 3D vertex for lit surfaces, with a normal and texture coordinates.
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Zeroable, Pod)]
+pub struct NormalVertex {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+}
+
+vulkano::impl_vertex!(TexturedVertex, position, uv);
+vulkano::impl_vertex!(ColouredTexturedVertex, position, colour, uv);
+vulkano::impl_vertex!(Vertex3D, position, colour);
+vulkano::impl_vertex!(NormalVertex, position, normal, uv);
+
+impl TexturedVertex {
+    pub fn new(position: Vec2, uv: Vec2) -> TexturedVertex {
+        TexturedVertex { position, uv }
+    }
+}
+
+impl ColouredTexturedVertex {
+    pub fn new(position: Vec2, colour: Colour, uv: Vec2) -> ColouredTexturedVertex {
+        ColouredTexturedVertex { position, colour, uv }
+    }
+}
+
+impl Vertex3D {
+    pub fn new(position: Vec3, colour: Colour) -> Vertex3D {
+        Vertex3D { position, colour }
+    }
+}
+
+impl NormalVertex {
+    pub fn new(position: Vec3, normal: Vec3, uv: Vec2) -> NormalVertex {
+        NormalVertex { position, normal, uv }
+    }
+}
@@ -1,6 +1,6 @@
 mod application;
 
-#[path="crates/geometry.rs"]
+#[path="crates/geometry/mod.rs"]
 mod geometry;
 
 use winit::event::VirtualKeyCode;
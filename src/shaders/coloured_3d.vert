@@ -0,0 +1,20 @@
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec4 colour;
+
+layout(location = 0) out vec4 fragColour;
+
+layout(set = 0, binding = 0) uniform Frame {
+    mat4 view_projection;
+} frame;
+
+layout(push_constant) uniform Draw {
+    mat4 model;
+    vec4 tint;
+} draw;
+
+void main() {
+    gl_Position = frame.view_projection * draw.model * vec4(position, 1.0);
+    fragColour = colour * draw.tint;
+}
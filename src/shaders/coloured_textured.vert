@@ -0,0 +1,23 @@
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec4 colour;
+layout(location = 2) in vec2 uv;
+
+layout(location = 0) out vec4 fragColour;
+layout(location = 1) out vec2 fragUv;
+
+layout(set = 0, binding = 0) uniform Frame {
+    mat4 view_projection;
+} frame;
+
+layout(push_constant) uniform Draw {
+    mat4 model;
+    vec4 tint;
+} draw;
+
+void main() {
+    gl_Position = frame.view_projection * draw.model * vec4(position, 0.0, 1.0);
+    fragColour = colour * draw.tint;
+    fragUv = uv;
+}
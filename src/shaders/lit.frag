@@ -0,0 +1,14 @@
+#version 450
+
+layout(location = 0) in vec3 fragNormal;
+layout(location = 1) in vec2 fragUv;
+layout(location = 2) in vec4 fragTint;
+layout(location = 0) out vec4 outColour;
+
+const vec3 light_direction = vec3(0.3, -0.5, 0.8);
+const float ambient = 0.2;
+
+void main() {
+    float diffuse = max(dot(normalize(fragNormal), normalize(light_direction)), 0.0);
+    outColour = vec4(vec3(ambient + diffuse * (1.0 - ambient)), 1.0) * fragTint;
+}
@@ -0,0 +1,26 @@
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+
+layout(location = 0) out vec3 fragNormal;
+layout(location = 1) out vec2 fragUv;
+layout(location = 2) out vec4 fragTint;
+
+layout(set = 0, binding = 0) uniform Frame {
+    mat4 view_projection;
+} frame;
+
+layout(push_constant) uniform Draw {
+    mat4 model;
+    vec4 tint;
+} draw;
+
+void main() {
+    gl_Position = frame.view_projection * draw.model * vec4(position, 1.0);
+    // only correct for models scaled the same along every axis
+    fragNormal = mat3(draw.model) * normal;
+    fragUv = uv;
+    fragTint = draw.tint;
+}
@@ -1,11 +1,20 @@
 #version 450
 
 layout(location = 0) in vec2 position;
-layout(location = 1) in vec3 colour;
+layout(location = 1) in vec4 colour;
 
-layout(location = 0) out vec3 fragColour;
+layout(location = 0) out vec4 fragColour;
+
+layout(set = 0, binding = 0) uniform Frame {
+    mat4 view_projection;
+} frame;
+
+layout(push_constant) uniform Draw {
+    mat4 model;
+    vec4 tint;
+} draw;
 
 void main() {
-    gl_Position = vec4(position, 0.0, 1.0);
-    fragColour = colour;
+    gl_Position = frame.view_projection * draw.model * vec4(position, 0.0, 1.0);
+    fragColour = colour * draw.tint;
 }
\ No newline at end of file
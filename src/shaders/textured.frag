@@ -0,0 +1,11 @@
+#version 450
+
+layout(location = 0) in vec2 fragUv;
+layout(location = 1) in vec4 fragTint;
+layout(location = 0) out vec4 outColour;
+
+layout(set = 1, binding = 0) uniform sampler2D tex;
+
+void main() {
+    outColour = texture(tex, fragUv) * fragTint;
+}
@@ -0,0 +1,22 @@
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 uv;
+
+layout(location = 0) out vec2 fragUv;
+layout(location = 1) out vec4 fragTint;
+
+layout(set = 0, binding = 0) uniform Frame {
+    mat4 view_projection;
+} frame;
+
+layout(push_constant) uniform Draw {
+    mat4 model;
+    vec4 tint;
+} draw;
+
+void main() {
+    gl_Position = frame.view_projection * draw.model * vec4(position, 0.0, 1.0);
+    fragUv = uv;
+    fragTint = draw.tint;
+}